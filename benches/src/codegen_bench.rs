@@ -0,0 +1,68 @@
+use compiler::codegen::{CodeGenInfo, CodeGenerator, FmtCodeGen};
+use compiler::compiler::{get_base_passes, parse_to_ir, CompileOption};
+use compiler::converter::BaseRoot;
+use compiler::transformer::{BaseTransformer, Transformer};
+use compiler::SFCInfo;
+
+use criterion::{criterion_group, criterion_main};
+use criterion::{BatchSize, BenchmarkId, Criterion};
+
+fn nested(depth: usize) -> String {
+    let open = "<div :id=\"id\" class=\"box\">".repeat(depth);
+    let close = "</div>".repeat(depth);
+    format!("{}{{{{ msg }}}}{}", open, close)
+}
+
+fn many_props(count: usize) -> String {
+    let props: String = (0..count)
+        .map(|i| format!(" a{0}=\"x\" :b{0}=\"y{0}\" @c{0}=\"z{0}\"", i))
+        .collect();
+    format!("<div{}/>", props)
+}
+
+fn large_v_for(count: usize) -> String {
+    let item = r#"<li v-for="item in list" :key="item.id" :class="{ active: item.ok }">
+  {{ item.name }}<span v-if="item.tag">{{ item.tag }}</span><em v-else>none</em>
+</li>"#;
+    format!("<ul>{}</ul>", item.repeat(count))
+}
+
+/// IR is consumed by codegen so it is rebuilt outside of measurement.
+fn build_ir<'a>(source: &'a str, option: &CompileOption, info: &'a SFCInfo<'a>) -> BaseRoot<'a> {
+    let mut ir = parse_to_ir(source, option, info).expect("bench template should be valid");
+    let passes = get_base_passes(info, option);
+    BaseTransformer::transform(&mut ir, passes);
+    ir
+}
+
+fn codegen_throughput(c: &mut Criterion) {
+    let option = CompileOption {
+        is_dev: false,
+        ..Default::default()
+    };
+    let sfc_info = SFCInfo::default();
+    let cases = [
+        ("nested", nested(50)),
+        ("many_props", many_props(100)),
+        ("large_v_for", large_v_for(50)),
+    ];
+    for (name, source) in cases.iter() {
+        c.bench_with_input(BenchmarkId::new("codegen", name), source, |b, s| {
+            let generator = FmtCodeGen::new(option.codegen());
+            b.iter_batched(
+                || build_ir(s, &option, &sfc_info),
+                |ir| {
+                    let info = CodeGenInfo {
+                        writer: String::new(),
+                        sfc_info: &sfc_info,
+                    };
+                    generator.generate(ir, info).unwrap()
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+}
+
+criterion_group!(benches, codegen_throughput);
+criterion_main!(benches);
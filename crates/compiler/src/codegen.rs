@@ -1,14 +1,19 @@
 mod code_writer;
+mod estimate;
 
 use crate::converter::BaseRoot;
-use crate::SFCInfo;
+use crate::flags::RuntimeHelper;
+use crate::{SFCInfo, SourceLocation};
+use crate::util::no;
 use crate::ir::{self as C, ConvertInfo, IRNode, IRRoot};
-use code_writer::CodeWriter;
+use code_writer::{CodeWriter, WriteAdaptor};
+pub use estimate::estimate_size;
 
 use smallvec::{smallvec, SmallVec};
 use std::marker::PhantomData;
 use std::{
     borrow::Cow,
+    fmt,
     rc::Rc,
     io::{self, Write as ioWrite},
 };
@@ -22,7 +27,7 @@ pub trait CodeGenerator {
     fn generate<'a>(&self, node: Self::IR<'a>, info: Self::Info<'a>) -> Self::Output;
 }
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Hash)]
 pub enum ScriptMode {
     Function {
         /// Transform expressions like {{ foo }} to `_ctx.foo`.
@@ -43,22 +48,118 @@ pub enum ScriptMode {
         /// @default 'vue'
         runtime_module_name: String,
     },
+    /// CommonJS output for legacy Node tooling. Helpers are required
+    /// and render is exported by `module.exports = { render }`.
+    Cjs {
+        /// Customize where to require runtime helpers from.
+        /// @default 'vue'
+        runtime_module_name: String,
+    },
+}
+
+/// How the render function is exported in module mode.
+#[derive(PartialEq, Eq, Clone, Hash)]
+pub enum ExportStyle {
+    /// `function render(...)`
+    None,
+    /// `export function render(...)`
+    Named,
+    /// `export default function render(...)`
+    Default,
+}
+
+/// Which kind of render function the generator emits.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum CodegenTarget {
+    /// `function render(_ctx, _cache)` for client side rendering.
+    Dom,
+    /// Unnamed arrow `(_ctx, _cache) => {}` returned from setup() or
+    /// used as functional component. Inline SFC render is always an arrow.
+    Functional,
+}
+impl CodegenTarget {
+    /// Inline SFC render is returned from setup() so it is an arrow.
+    pub fn resolve(self, sfc_info: &SFCInfo) -> Self {
+        if sfc_info.inline {
+            CodegenTarget::Functional
+        } else {
+            self
+        }
+    }
+    /// None for the unnamed arrow function
+    pub fn render_name(&self) -> Option<&'static str> {
+        match self {
+            CodegenTarget::Dom => Some("render"),
+            CodegenTarget::Functional => None,
+        }
+    }
+    /// Written before the render function in module mode.
+    /// The unnamed arrow can only be a default export.
+    pub fn export_keyword(&self, style: &ExportStyle) -> &'static str {
+        match (self, style) {
+            (CodegenTarget::Functional, _) => "export default ",
+            (CodegenTarget::Dom, ExportStyle::Default) => "export default ",
+            (CodegenTarget::Dom, ExportStyle::Named) => "export ",
+            (CodegenTarget::Dom, ExportStyle::None) => "",
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct CodeGenerateOption {
     pub is_dev: bool,
     pub mode: ScriptMode,
+    /// Only used in module mode. Function mode always returns render.
+    pub export_style: ExportStyle,
+    /// Emit only the render body, i.e. `return ...`, without function
+    /// signature, helper preamble or with-block. Embedder provides them.
+    pub body_only: bool,
+    /// Omit `_ctx`/`_cache` from render params if the body never uses them.
+    /// Only applies without with-scope and extra binding params.
+    pub elide_unused_params: bool,
+    /// Append `render.__file = "<self_name>"` in dev module mode.
+    pub emit_file_name: bool,
     pub source_map: bool,
     pub helper_strs: &'static [&'static str],
+    /// Import source of custom helpers. None means the runtime module.
+    pub helper_source: fn(RuntimeHelper) -> Option<&'static str>,
+    /// Consulted before the default helper name.
+    pub helper_name_override: fn(RuntimeHelper) -> Option<&'static str>,
+    /// Void elements like `<img>` never receive children.
+    pub is_void_tag: fn(&str) -> bool,
+    /// Report helpers written without import/destructure as errors
+    /// instead of debug assertion. Useful for custom passes' development.
+    pub check_helpers: bool,
+    /// Emit everything in one line without newline and indentation.
+    pub compact: bool,
+    /// Emit `/* line N */` before vnode calls in dev.
+    pub line_comments: bool,
+    /// Emit `/* dynamic children: 0, 2 */` before block children in dev.
+    pub dynamic_children_comments: bool,
+    pub target: CodegenTarget,
+    /// Emit `/* @__NO_SIDE_EFFECTS__ */` before the module render function.
+    pub no_side_effects: bool,
+    /// Emit `// hoists`, `// assets` and `// return` before sections in dev.
+    pub section_comments: bool,
+    /// Wrap function mode output in a self-executing `(function () {...})()`.
+    pub iife: bool,
+    /// Assign the IIFE result to this target, e.g. `window.render`. Implies iife.
+    pub global_var: Option<String>,
+    /// Wrap call arguments and array items onto indented lines beyond this width.
+    /// 0 disables wrapping.
+    pub max_line_length: usize,
 }
 impl CodeGenerateOption {
+    fn use_iife(&self) -> bool {
+        let is_function = matches!(self.mode, ScriptMode::Function { .. });
+        (self.iife || self.global_var.is_some()) && is_function && !self.body_only
+    }
     fn use_with_scope(&self) -> bool {
         match self.mode {
             ScriptMode::Function {
                 prefix_identifier, ..
             } => !prefix_identifier,
-            ScriptMode::Module { .. } => false,
+            ScriptMode::Module { .. } | ScriptMode::Cjs { .. } => false,
         }
     }
 }
@@ -70,11 +171,67 @@ impl Default for CodeGenerateOption {
                 prefix_identifier: false,
                 runtime_global_name: "Vue".into(),
             },
+            export_style: ExportStyle::None,
+            body_only: false,
+            elide_unused_params: false,
+            emit_file_name: false,
             source_map: false,
             helper_strs: &[],
+            helper_source: |_| None,
+            helper_name_override: |_| None,
+            is_void_tag: no,
+            check_helpers: false,
+            compact: false,
+            line_comments: false,
+            dynamic_children_comments: false,
+            target: CodegenTarget::Dom,
+            no_side_effects: false,
+            section_comments: false,
+            iife: false,
+            global_var: None,
+            max_line_length: 80,
+        }
+    }
+}
+
+/// Non-fatal diagnostic found during generation, e.g. dropped children.
+/// Tooling can surface it as lint feedback without failing the build.
+pub struct CodegenWarning {
+    pub msg: &'static str,
+    /// Template span of the offending element.
+    pub location: SourceLocation,
+}
+
+/// Fatal inconsistency found during generation. The output is broken.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CodegenError {
+    /// Helper is used in code but missing in the import/destructure list.
+    MissingHelperImport(RuntimeHelper),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHelperImport(rh) => {
+                write!(f, "Runtime helper {:?} is used but not imported.", rh)
+            }
         }
     }
 }
+impl std::error::Error for CodegenError {}
+
+#[derive(Default)]
+pub struct CodegenResult {
+    pub warnings: Vec<CodegenWarning>,
+    /// Missing helpers with `check_helpers` option.
+    pub errors: Vec<CodegenError>,
+    /// Module level constants of hoisted nodes in declaration order.
+    /// Names are unique in one output, bundlers can use them to dedupe.
+    pub hoisted_names: Vec<String>,
+    /// Number of `_cache` slots used by cached handlers, v-once and v-memo.
+    /// Runtime can pre-size the render cache array with it.
+    pub cache_count: usize,
+}
 
 pub trait CoreCodeGenerator<T: ConvertInfo> {
     type Written;
@@ -90,6 +247,7 @@ pub trait CoreCodeGenerator<T: ConvertInfo> {
             IR::AlterableSlot(a) => self.generate_alterable_slot(a),
             IR::CacheNode(cache) => self.generate_cache(cache),
             IR::CommentCall(c) => self.generate_comment(c),
+            IR::Hoisted(i) => self.generate_hoisted(i),
         }
     }
     fn generate_prologue(&mut self, t: &mut IRRoot<T>) -> Self::Written;
@@ -104,17 +262,34 @@ pub trait CoreCodeGenerator<T: ConvertInfo> {
     fn generate_cache(&mut self, c: C::CacheIR<T>) -> Self::Written;
     fn generate_js_expr(&mut self, e: T::JsExpression) -> Self::Written;
     fn generate_comment(&mut self, c: T::CommentType) -> Self::Written;
+    fn generate_hoisted(&mut self, index: usize) -> Self::Written;
 }
 
+/// Code generator writing to `fmt::Write`, e.g. `&mut String`.
+/// This is the primary path and does not require `std::io`.
+pub struct FmtCodeGen<T: fmt::Write> {
+    option: Rc<CodeGenerateOption>,
+    pd: PhantomData<T>,
+}
+/// Code generator writing to `io::Write`, adapted on top of FmtCodeGen.
 pub struct CodeGen<T: ioWrite> {
     option: Rc<CodeGenerateOption>,
     pd: PhantomData<T>,
 }
-pub struct CodeGenInfo<'a, T: ioWrite> {
+pub struct CodeGenInfo<'a, T> {
     pub writer: T,
     pub sfc_info: &'a SFCInfo<'a>,
 }
 
+impl<T: fmt::Write> FmtCodeGen<T> {
+    pub fn new(option: CodeGenerateOption) -> Self {
+        Self {
+            option: Rc::new(option),
+            pd: PhantomData,
+        }
+    }
+}
+
 impl<T: ioWrite> CodeGen<T> {
     pub fn new(option: CodeGenerateOption) -> Self {
         Self {
@@ -124,15 +299,39 @@ impl<T: ioWrite> CodeGen<T> {
     }
 }
 
+fn generate_fmt<'a, T: fmt::Write>(
+    option: &Rc<CodeGenerateOption>,
+    root: BaseRoot<'a>,
+    info: CodeGenInfo<'a, T>,
+) -> Result<CodegenResult, fmt::Error> {
+    let mut imp = CodeWriter::new(info.writer, option.clone(), info.sfc_info);
+    imp.generate_root(root)?;
+    Ok(imp.take_result())
+}
+
+impl<T: fmt::Write> CodeGenerator for FmtCodeGen<T> {
+    type IR<'a> = BaseRoot<'a>;
+    type Info<'a> = CodeGenInfo<'a, T>;
+    type Output = Result<CodegenResult, fmt::Error>;
+
+    fn generate<'a>(&self, root: BaseRoot<'a>, info: Self::Info<'a>) -> Self::Output {
+        generate_fmt(&self.option, root, info)
+    }
+}
+
 impl<T: ioWrite> CodeGenerator for CodeGen<T> {
     type IR<'a> = BaseRoot<'a>;
     type Info<'a> = CodeGenInfo<'a, T>;
-    type Output = io::Result<()>;
+    type Output = io::Result<CodegenResult>;
 
     fn generate<'a>(&self, root: BaseRoot<'a>, info: Self::Info<'a>) -> Self::Output {
-        let mut imp = CodeWriter::new(info.writer, self.option.clone(), info.sfc_info);
-        imp.generate_root(root)
-            .map_err(|_| imp.writer.get_io_error())
+        let CodeGenInfo { writer, sfc_info } = info;
+        let mut adaptor = WriteAdaptor::new(writer);
+        let info = CodeGenInfo {
+            writer: &mut adaptor,
+            sfc_info,
+        };
+        generate_fmt(&self.option, root, info).map_err(|_| adaptor.get_io_error())
     }
 }
 
@@ -1,20 +1,25 @@
-use super::{CodeGenerateOption, ScriptMode, CoreCodeGenerator};
+use super::{
+    CodeGenerateOption, CodegenError, CodegenResult, CodegenTarget, CodegenWarning, ScriptMode,
+    CoreCodeGenerator,
+};
 use crate::flags::{HelperCollector, PatchFlag, RuntimeHelper as RH, SlotFlag};
 use crate::converter::v_on::get_handler_type;
 use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot, TopScope};
 use crate::transformer::{
     BaseFor, BaseIf, BaseRenderSlot, BaseSlotFn, BaseText, BaseVNode, BaseVSlot, BaseCache,
 };
-use crate::ir::{self as C, IRNode, JsExpr as Js, RenderSlotIR, RuntimeDir, VNodeIR, HandlerType};
+use crate::ir::{
+    self as C, IRNode, IfBranch, JsExpr as Js, RenderSlotIR, RuntimeDir, VNodeIR, HandlerType,
+};
 use crate::util::{get_vnode_call_helper, is_simple_identifier, VStr};
-use crate::SFCInfo;
+use crate::{SFCInfo, SourceLocation};
 
 use smallvec::SmallVec;
 use std::{
     fmt::{self, Write},
     io::{self, Write as ioWrite},
     rc::Rc,
-    iter,
+    iter, vec,
 };
 
 type Output = fmt::Result;
@@ -24,7 +29,7 @@ pub struct WriteAdaptor<T: ioWrite> {
     io_error: Option<io::Error>,
 }
 impl<T: ioWrite> WriteAdaptor<T> {
-    fn new(inner: T) -> Self {
+    pub fn new(inner: T) -> Self {
         Self {
             inner,
             io_error: None,
@@ -50,8 +55,26 @@ impl<T: ioWrite> fmt::Write for WriteAdaptor<T> {
     }
 }
 
-pub struct CodeWriter<'a, T: ioWrite> {
-    pub writer: WriteAdaptor<T>,
+/// Counts the width of one line, fails once past the limit or at a line break.
+struct LineMeter {
+    width: usize,
+    limit: usize,
+}
+
+impl fmt::Write for LineMeter {
+    fn write_str(&mut self, s: &str) -> Output {
+        let line = s.split('\n').next().unwrap_or("");
+        self.width += line.len();
+        if line.len() < s.len() || self.width > self.limit {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct CodeWriter<'a, T: Write> {
+    pub writer: T,
     option: Rc<CodeGenerateOption>,
     sfc_info: &'a SFCInfo<'a>,
     indent_level: usize,
@@ -59,11 +82,19 @@ pub struct CodeWriter<'a, T: ioWrite> {
     cache_count: usize,
     in_alterable: bool,
     helpers: HelperCollector,
+    missing_helpers: HelperCollector,
+    warnings: Vec<CodegenWarning>,
+    errors: Vec<CodegenError>,
+    hoisted_names: Vec<String>,
+    /// set when a _ctx prefixed identifier is written
+    ctx_used: bool,
+    /// width in bytes of the current output line
+    column: usize,
 }
-impl<'a, T: ioWrite> CodeWriter<'a, T> {
+impl<'a, T: Write> CodeWriter<'a, T> {
     pub fn new(writer: T, option: Rc<CodeGenerateOption>, sfc_info: &'a SFCInfo<'a>) -> Self {
         Self {
-            writer: WriteAdaptor::new(writer),
+            writer,
             option,
             sfc_info,
             indent_level: 0,
@@ -71,17 +102,48 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             cache_count: 0,
             in_alterable: false,
             helpers: Default::default(),
+            missing_helpers: Default::default(),
+            warnings: vec![],
+            errors: vec![],
+            hoisted_names: vec![],
+            ctx_used: false,
+            column: 0,
         }
     }
+    pub fn take_result(&mut self) -> CodegenResult {
+        let missing = std::mem::take(&mut self.missing_helpers);
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.extend(missing.into_iter().map(CodegenError::MissingHelperImport));
+        CodegenResult {
+            warnings: std::mem::take(&mut self.warnings),
+            hoisted_names: std::mem::take(&mut self.hoisted_names),
+            cache_count: self.cache_count,
+            errors,
+        }
+    }
+}
+
+/// routes write! through write_str to track the column
+impl<'a, T: Write> fmt::Write for CodeWriter<'a, T> {
+    fn write_str(&mut self, s: &str) -> Output {
+        CodeWriter::write_str(self, s)
+    }
 }
 
-impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T> {
+impl<'a, T: Write> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T> {
     type Written = Output;
     fn generate_prologue(&mut self, root: &mut BaseRoot<'a>) -> Output {
-        self.generate_preamble(&mut root.top_scope)?;
-        self.generate_function_signature()?;
-        self.generate_with_scope()?;
+        if self.option.body_only {
+            // hoists are still needed since they are local to the output
+            self.gen_hoist(&mut root.top_scope)?;
+        } else {
+            self.generate_preamble(&mut root.top_scope)?;
+            self.generate_function_signature()?;
+            self.generate_with_scope()?;
+        }
         self.generate_assets(&root.top_scope)?;
+        self.gen_for_classes(&mut root.top_scope)?;
+        self.gen_section_comment("return")?;
         self.write_str("return ")
     }
     fn generate_epilogue(&mut self) -> Output {
@@ -90,7 +152,13 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
             self.write_str("}")?;
         }
         debug_assert_eq!(self.indent_level, 0);
-        Ok(())
+        let is_cjs = matches!(self.option.mode, ScriptMode::Cjs { .. });
+        let render_name = self.target().render_name();
+        if let Some(name) = render_name.filter(|_| is_cjs && !self.option.body_only) {
+            self.newline()?;
+            write!(self, "module.exports = {{ {} }}", name)?;
+        }
+        self.gen_file_name()
     }
     fn generate_text(&mut self, t: BaseText<'a>) -> Output {
         if t.fast_path {
@@ -106,34 +174,7 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         self.write_str(")")
     }
     fn generate_if(&mut self, i: BaseIf<'a>) -> Output {
-        let mut indent = 0;
-        for branch in i.branches {
-            if branch.condition.is_none() {
-                // should use into_inner but it's unstable
-                self.generate_ir(*branch.child)?;
-                return self.flush_deindent(indent);
-            }
-            indent += 1;
-            let condition = branch.condition.unwrap();
-            self.write_str("(")?;
-            self.generate_js_expr(condition)?;
-            self.write_str(")")?;
-            self.indent()?;
-            self.write_str("? ")?;
-            self.generate_ir(*branch.child)?;
-            self.newline()?;
-            self.write_str(": ")?;
-        }
-        if !self.in_alterable {
-            // generate default v-else comment
-            let s = if self.option.is_dev { "'v-if'" } else { "''" };
-            let comment = Js::Call(RH::CREATE_COMMENT, vec![Js::Src(s), Js::Src("true")]);
-            self.generate_js_expr(comment)?;
-        } else {
-            // generate undefined for alterable_slots
-            self.write_str("undefined")?;
-        }
-        self.flush_deindent(indent)
+        self.gen_if_branches(i.branches.into_iter())
     }
     fn generate_for(&mut self, f: BaseFor<'a>) -> Output {
         // skip block creation or Fragment in alterable_slots
@@ -149,6 +190,7 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         })
     }
     fn generate_vnode(&mut self, v: BaseVNode<'a>) -> Output {
+        self.gen_line_comment(&v.location)?;
         self.gen_vnode_with_dir(v)
     }
     fn generate_slot_outlet(&mut self, r: BaseRenderSlot<'a>) -> Output {
@@ -184,19 +226,20 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         use C::CacheKind as CK;
         match c.kind {
             CK::Once => {
-                write!(self.writer, "_cache[{}] || (", self.cache_count)?;
+                let idx = self.cache_count;
+                write!(self, "_cache[{}] || (", idx)?;
                 self.indent()?;
                 self.write_helper(RH::SET_BLOCK_TRACKING)?;
                 self.write_str("(-1),")?;
                 self.newline()?;
-                write!(self.writer, "_cache[{}] = ", self.cache_count)?;
+                write!(self, "_cache[{}] = ", idx)?;
                 self.generate_ir(*c.child)?;
                 self.write_str(",")?;
                 self.newline()?;
                 self.write_helper(RH::SET_BLOCK_TRACKING)?;
                 self.write_str("(1),")?;
                 self.newline()?;
-                write!(self.writer, "_cache[{}]", self.cache_count)?;
+                write!(self, "_cache[{}]", idx)?;
                 self.deindent()?;
                 self.write_str(")")?;
             }
@@ -204,15 +247,17 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
                 self.write_helper(RH::WITH_MEMO)?;
                 self.write_str("(")?;
                 self.generate_js_expr(expr)?;
-                self.write_str(", () => ")?;
-                self.generate_ir(*c.child)?;
-                write!(self.writer, ", _cache, {})", self.cache_count)?;
+                self.write_str(", ")?;
+                let child = *c.child;
+                self.gen_arrow(iter::empty(), false, |gen| gen.generate_ir(child))?;
+                let idx = self.cache_count;
+                write!(self, ", _cache, {})", idx)?;
             }
             CK::MemoInVFor { expr, v_for_key } => {
                 self.write_str("const _memo=(")?;
                 self.generate_js_expr(expr)?;
                 self.write_str(")")?;
-                self.newline()?;
+                self.end_stmt()?;
                 self.write_str("if (_cached")?;
                 if let Some(key) = v_for_key {
                     self.write_str(" && _cache.key === ")?;
@@ -221,12 +266,12 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
                 self.write_str(" && ")?;
                 self.write_helper(RH::IS_MEMO_SAME)?;
                 self.write_str("(_cached, _memo)) return _cached")?;
-                self.newline()?;
+                self.end_stmt()?;
                 self.write_str("const _item = ")?;
                 self.generate_ir(*c.child)?;
-                self.newline()?;
+                self.end_stmt()?;
                 self.write_str("_item.memo = _memo")?;
-                self.newline()?;
+                self.end_stmt()?;
                 self.write_str("return _item")?;
             }
         }
@@ -236,9 +281,14 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
     fn generate_js_expr(&mut self, expr: Js<'a>) -> Output {
         match expr {
             Js::Src(s) | Js::Param(s) => self.write_str(s),
-            Js::Num(n) => write!(self.writer, "{}", n),
-            Js::StrLit(mut l) => l.be_js_str().write_to(&mut self.writer),
-            Js::Simple(e, _) => e.write_to(&mut self.writer),
+            Js::Num(n) => write!(self, "{}", n),
+            Js::StrLit(mut l) => l.be_js_str().write_to(&mut *self),
+            // fast path: most simple expressions are plain identifiers
+            Js::Simple(e, _) if e.ops.is_empty() => self.write_str(e.raw),
+            Js::Simple(e, _) => {
+                self.ctx_used |= VStr::is_ctx_prefixed(&e);
+                e.write_to(&mut *self)
+            }
             Js::Symbol(s) => self.write_helper(s),
             Js::Props(p) => self.gen_obj_props(p, |gen, v| gen.generate_js_expr(v)),
             Js::Compound(v) => {
@@ -249,18 +299,19 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
             }
             Js::Array(a) => {
                 self.write_str("[")?;
-                self.gen_list(a)?;
+                self.gen_wrapped_list(a)?;
                 self.write_str("]")
             }
             Js::Call(c, args) => {
                 self.write_helper(c)?;
                 self.write_str("(")?;
-                self.gen_list(args)?;
+                self.gen_wrapped_list(args)?;
                 self.write_str(")")
             }
             Js::FuncSimple { src, cache, .. } => {
                 let ty = get_handler_type(src);
-                gen_handler(self, ty, cache, |gen| src.write_to(&mut gen.writer))
+                self.ctx_used |= VStr::is_ctx_prefixed(&src);
+                gen_handler(self, ty, cache, |gen| src.write_to(&mut *gen))
             }
             Js::FuncCompound {
                 body, ty, cache, ..
@@ -290,32 +341,97 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         Ok(())
     }
     fn generate_comment(&mut self, c: &'a str) -> Output {
-        let comment = Js::str_lit(c);
-        let call = Js::Call(RH::CREATE_COMMENT, vec![comment]);
-        self.generate_js_expr(call)
+        // authored comment is a normal vnode
+        self.gen_comment_vnode(Js::str_lit(c), false)
+    }
+    fn generate_hoisted(&mut self, index: usize) -> Output {
+        write!(self, "_hoisted_{}", index)
     }
 }
 
-impl<'a, T: ioWrite> CodeWriter<'a, T> {
+impl<'a, T: Write> CodeWriter<'a, T> {
     pub fn generate_root(&mut self, mut root: BaseRoot<'a>) -> Output {
         // get top scope entities
         self.helpers = root.top_scope.helpers.clone();
-
-        self.generate_prologue(&mut root)?;
-        if root.body.is_empty() {
-            self.write_str("null")?;
+        self.gen_iife_open()?;
+        if self.can_elide_params() {
+            self.generate_root_elided(root)?;
         } else {
-            let ir = if root.body.len() == 1 {
-                root.body.pop().unwrap()
-            } else {
-                IRNode::VNodeCall(VNodeIR {
-                    tag: Js::Symbol(RH::FRAGMENT),
-                    children: root.body,
-                    ..VNodeIR::default()
-                })
-            };
-            self.generate_ir(ir)?;
+            self.generate_prologue(&mut root)?;
+            self.generate_body(root.body)?;
+            self.generate_epilogue()?;
         }
+        self.gen_iife_close()
+    }
+    /// function mode returns render, so the IIFE evaluates to render
+    fn gen_iife_open(&mut self) -> Output {
+        if !self.option.use_iife() {
+            return Ok(());
+        }
+        let option = self.option.clone();
+        if let Some(global) = &option.global_var {
+            write!(self, "{} = ", global)?;
+        }
+        self.write_str("(function () {")?;
+        self.newline()
+    }
+    fn gen_iife_close(&mut self) -> Output {
+        if !self.option.use_iife() {
+            return Ok(());
+        }
+        self.newline()?;
+        self.write_str("})()")
+    }
+    fn generate_body(&mut self, mut body: Vec<BaseIR<'a>>) -> Output {
+        if body.is_empty() {
+            return self.write_str("null");
+        }
+        let ir = if body.len() == 1 {
+            body.pop().unwrap()
+        } else {
+            IRNode::VNodeCall(VNodeIR {
+                tag: Js::Symbol(RH::FRAGMENT),
+                children: body,
+                ..VNodeIR::default()
+            })
+        };
+        self.generate_ir(ir)
+    }
+    fn can_elide_params(&self) -> bool {
+        let info = self.sfc_info;
+        self.option.elide_unused_params
+            && !self.option.body_only
+            && !self.option.use_with_scope()
+            && (info.binding_metadata.is_empty() || info.inline)
+    }
+    /// render body is generated first since signature depends on its params usage
+    fn generate_root_elided(&mut self, mut root: BaseRoot<'a>) -> Output {
+        let mut body = CodeWriter::new(String::new(), self.option.clone(), self.sfc_info);
+        body.helpers = self.helpers.clone();
+        // same indent as the body after function signature
+        body.indent_level = 1;
+        body.column = 2;
+        // v-for classes may use _ctx too, so they are generated with the body
+        body.gen_for_classes(&mut root.top_scope)?;
+        body.gen_section_comment("return")?;
+        body.write_str("return ")?;
+        body.generate_body(std::mem::take(&mut root.body))?;
+        self.cache_count = body.cache_count;
+        self.ctx_used = body.ctx_used;
+        self.warnings.append(&mut body.warnings);
+        for rh in body.missing_helpers {
+            self.missing_helpers.collect(rh);
+        }
+        // every _cache access allocates a cache slot
+        let args = match (self.ctx_used, self.cache_count > 0) {
+            (_, true) => "_ctx, _cache",
+            (true, false) => "_ctx",
+            (false, false) => "",
+        };
+        self.generate_preamble(&mut root.top_scope)?;
+        self.gen_render_signature(args)?;
+        self.generate_assets(&root.top_scope)?;
+        self.write_str(&body.writer)?;
         self.generate_epilogue()
     }
     /// for import helpers or hoist that not in function
@@ -328,6 +444,9 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
                 runtime_global_name,
                 ..
             } => self.gen_function_preamble(top, runtime_global_name),
+            ScriptMode::Cjs {
+                runtime_module_name,
+            } => self.gen_cjs_preamble(top, runtime_module_name),
         }
     }
     fn gen_function_preamble(&mut self, top: &mut TopScope<'a>, global_name: &str) -> Output {
@@ -336,7 +455,7 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             if self.option.use_with_scope() {
                 self.write_str("const _Vue = ")?;
                 self.write_str(global_name)?;
-                self.newline()?;
+                self.end_stmt()?;
                 // helpers are declared inside with block, but hoists
                 // are lifted out so we need extract hoist helper here.
                 if !top.hoists.is_empty() {
@@ -352,6 +471,9 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.newline()?;
         self.write_str("return ")
     }
+    fn target(&self) -> CodegenTarget {
+        self.option.target.resolve(self.sfc_info)
+    }
     fn should_gen_scope_id(&self) -> bool {
         self.sfc_info.scope_id.is_some() && matches!(self.option.mode, ScriptMode::Module { .. })
     }
@@ -361,18 +483,61 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             self.helpers.collect(RH::POP_SCOPE_ID);
         }
         if !self.helpers.is_empty() {
-            let helpers = self.helpers.clone();
-            self.gen_helper_import(helpers, module_name)?;
+            self.gen_module_helper_imports(module_name, Self::gen_helper_import)?;
             self.newline()?;
         }
         self.gen_imports(top)?;
         self.gen_hoist(top)?;
         self.newline()?;
-        if self.sfc_info.inline {
-            self.write_str("export ")
-        } else {
-            Ok(())
+        // bundlers read the annotation before export or function keyword
+        let target = self.target();
+        if self.option.no_side_effects && target == CodegenTarget::Dom {
+            self.write_str("/* @__NO_SIDE_EFFECTS__ */ ")?;
+        }
+        self.write_str(target.export_keyword(&self.option.export_style))
+    }
+    /// helpers are destructured from require, render is exported in epilogue
+    fn gen_cjs_preamble(&mut self, top: &mut TopScope<'a>, module_name: &str) -> Output {
+        if !self.helpers.is_empty() {
+            self.gen_module_helper_imports(module_name, Self::gen_helper_require)?;
+            self.newline()?;
+        }
+        self.gen_hoist(top)?;
+        self.newline()?;
+        // arrow function has no name for module.exports = { render }
+        if self.target() == CodegenTarget::Functional {
+            self.write_str("module.exports = ")?;
+        }
+        Ok(())
+    }
+    /// custom helpers are imported from their own sources, grouped by source
+    fn gen_module_helper_imports<F>(&mut self, module_name: &str, gen_import: F) -> Output
+    where
+        F: Fn(&mut Self, HelperCollector, &str) -> Output,
+    {
+        let mut helpers = self.helpers.clone();
+        let mut custom: Vec<(&str, HelperCollector)> = vec![];
+        for rh in self.helpers.clone() {
+            let src = match (self.option.helper_source)(rh) {
+                Some(src) => src,
+                None => continue,
+            };
+            helpers.remove(rh);
+            if let Some((_, c)) = custom.iter_mut().find(|(s, _)| *s == src) {
+                c.collect(rh);
+            } else {
+                let mut c = HelperCollector::new();
+                c.collect(rh);
+                custom.push((src, c));
+            }
+        }
+        if !helpers.is_empty() {
+            gen_import(self, helpers, module_name)?;
+        }
+        for (src, c) in custom {
+            gen_import(self, c, src)?;
         }
+        Ok(())
     }
     fn gen_helper_import(&mut self, helpers: HelperCollector, from: &str) -> Output {
         self.write_str("import {")?;
@@ -382,7 +547,17 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.write_str("} from \"")?;
         self.write_str(from)?;
         self.write_str("\"")?;
-        self.newline()
+        self.end_stmt()
+    }
+    fn gen_helper_require(&mut self, helpers: HelperCollector, from: &str) -> Output {
+        self.write_str("const {")?;
+        self.indent()?;
+        self.gen_helper_import_list(helpers, ":")?;
+        self.deindent()?;
+        self.write_str("} = require(\"")?;
+        self.write_str(from)?;
+        self.write_str("\")")?;
+        self.end_stmt()
     }
     fn gen_helper_destruct(&mut self, helpers: HelperCollector, from: &str) -> Output {
         self.write_str("const {")?;
@@ -391,14 +566,15 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.deindent()?;
         self.write_str("} = ")?;
         self.write_str(from)?;
-        self.newline()
+        self.end_stmt()
     }
     fn gen_helper_import_list(&mut self, helpers: HelperCollector, sep: &str) -> Output {
         for rh in helpers.into_iter() {
-            self.write_str(rh.helper_str(self.option.helper_strs))?;
+            let name = self.helper_str(rh);
+            self.write_str(name)?;
             self.write_str(sep)?;
             self.write_str(" _")?;
-            self.write_str(rh.helper_str(self.option.helper_strs))?;
+            self.write_str(name)?;
             self.write_str(", ")?;
         }
         Ok(())
@@ -415,7 +591,7 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             self.generate_js_expr(impt.exp)?;
             self.write_str(" from ")?;
             self.write_str(impt.path)?;
-            self.newline()?;
+            self.end_stmt()?;
         }
         Ok(())
     }
@@ -423,16 +599,18 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         if top.hoists.is_empty() {
             return Ok(());
         }
+        self.gen_section_comment("hoists")?;
         let gen_scope_id = self.should_gen_scope_id();
         if gen_scope_id {
             // generate inlined withScopeId helper
             self.write_str("const _withScopeId = n => (")?;
             self.write_helper(RH::PUSH_SCOPE_ID)?;
-            let scope_id = self.sfc_info.scope_id.as_ref().unwrap();
-            write!(self.writer, "({}),n=n(),", scope_id)?;
+            let info = self.sfc_info;
+            let scope_id = info.scope_id.as_ref().unwrap();
+            write!(self, "({}),n=n(),", scope_id)?;
             self.write_helper(RH::POP_SCOPE_ID)?;
             self.write_str("(),n)")?;
-            self.newline()?;
+            self.end_stmt()?;
         }
         // take hoists
         let mut hoists = vec![];
@@ -444,28 +622,35 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             } else {
                 ""
             };
-            write!(self.writer, "const _hoisted_{} = {}", i, wrapper)?;
+            let name = format!("_hoisted_{}", i);
+            write!(self, "const {} = {}", name, wrapper)?;
+            self.hoisted_names.push(name);
             self.generate_ir(hoist)?;
             if scope_id_wrapper {
                 self.write_str(")")?;
             }
-            self.newline()?;
+            self.end_stmt()?;
         }
         Ok(())
     }
-    /// render() or ssrRender() and their parameters
+    /// render() and its parameters
     fn generate_function_signature(&mut self) -> Output {
-        let option = &self.sfc_info;
-        let args = if !option.binding_metadata.is_empty() && !option.inline {
+        let info = &self.sfc_info;
+        let with_bindings = !info.binding_metadata.is_empty() && !info.inline;
+        let args = if with_bindings {
             "_ctx, _cache, $props, $setup, $data, $options"
         } else {
             "_ctx, _cache"
         };
+        self.gen_render_signature(args)
+    }
+    fn gen_render_signature(&mut self, args: &str) -> Output {
         // NB: vue uses arrow func for inline mode.
         // but it makes no diff in Vue runtime implementation?
-        self.write_str("function render(")?;
-        self.write_str(args)?;
-        self.write_str(") {")?;
+        match self.target().render_name() {
+            Some(name) => write!(self, "function {}({}) {{", name, args)?,
+            None => write!(self, "({}) => {{", args)?,
+        }
         self.closing_brackets += 1;
         self.indent()
     }
@@ -489,16 +674,37 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
     fn generate_assets(&mut self, top: &TopScope<'a>) -> Output {
         if !top.components.is_empty() {
             self.newline()?;
+            self.gen_section_comment("assets")?;
             let components = top.components.iter().cloned();
             gen_assets(self, components, RH::RESOLVE_COMPONENT)?;
         }
         if !top.directives.is_empty() {
             self.newline()?;
+            if top.components.is_empty() {
+                self.gen_section_comment("assets")?;
+            }
             let directives = top.directives.iter().cloned();
             gen_assets(self, directives, RH::RESOLVE_DIRECTIVE)?;
         }
         Ok(())
     }
+    /// loop invariant v-for classes normalized once before render returns
+    fn gen_for_classes(&mut self, top: &mut TopScope<'a>) -> Output {
+        if top.classes.is_empty() {
+            return Ok(());
+        }
+        self.newline()?;
+        let classes = std::mem::take(&mut top.classes);
+        for (i, cls) in classes.into_iter().enumerate() {
+            match i {
+                0 => self.write_str("const _class = ")?,
+                n => write!(self, "const _class{} = ", n)?,
+            }
+            self.generate_js_expr(cls)?;
+            self.end_stmt()?;
+        }
+        Ok(())
+    }
 
     fn gen_concate_str(&mut self, t: SmallVec<[Js<'a>; 1]>) -> Output {
         let mut texts = t.into_iter();
@@ -515,13 +721,15 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
 
     fn generate_children(&mut self, children: Vec<BaseIR<'a>>) -> Output {
         debug_assert!(!children.is_empty());
-        let fast = if let IRNode::TextCall(t) = &children[0] {
-            t.fast_path
-        } else {
-            false
-        };
-        if fast {
+        let as_is = match &children[0] {
             // generate sole text node without []
+            IRNode::TextCall(t) => t.fast_path,
+            // component children are a slots object, e.g. { default: ... }
+            IRNode::VSlotUse(_) => true,
+            _ => false,
+        };
+        if as_is {
+            debug_assert_eq!(children.len(), 1);
             let ir = children.into_iter().next().unwrap();
             return self.generate_ir(ir);
         }
@@ -546,13 +754,25 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.generate_js_expr(f.source)?;
         self.write_str(", ")?;
         let p = f.parse_result;
-        let mut params = vec![Some(p.value), p.key, p.index];
+        // skipped alias like `(v, , i)` is empty and becomes placeholder
+        let alias = |a: Option<Js<'a>>| a.filter(|a| !matches!(a, Js::Param("")));
+        let mut params = vec![alias(Some(p.value)), alias(p.key), alias(p.index)];
+        // memo check needs statements, otherwise the vnode is the arrow's expression body
         if has_memo {
             params.push(Some(Js::Src("_cached")));
-            self.gen_func_expr(params, *f.child, /*need_return*/ false)?;
-            write!(self.writer, ", _cache, {}", self.cache_count - 1)?;
+            self.gen_func_expr(
+                params, *f.child, /*block*/ true, /*need_return*/ false,
+            )?;
+            write!(self, ", _cache, {}", self.cache_count - 1)?;
+        } else if self.in_alterable {
+            // slot object literal as expression body would be parsed as block
+            self.gen_func_expr(
+                params, *f.child, /*block*/ true, /*need_return*/ true,
+            )?;
         } else {
-            self.gen_func_expr(params, *f.child, /*need_return*/ true)?;
+            self.gen_func_expr(
+                params, *f.child, /*block*/ false, /*need_return*/ false,
+            )?;
         }
         self.write_str(")")
     }
@@ -561,6 +781,7 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         &mut self,
         params: Vec<Option<Js<'a>>>,
         body: BaseIR<'a>,
+        block: bool,
         need_return: bool,
     ) -> Output {
         const PLACE_HOLDER: &[&str] = &[
@@ -580,14 +801,29 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             .take(last)
             .enumerate()
             .map(|(i, o)| o.unwrap_or(Js::Src(PLACE_HOLDER[i])));
+        self.gen_arrow(normalized_params, block, |gen| {
+            if need_return {
+                gen.write_str("return ")?;
+            }
+            gen.generate_ir(body)
+        })
+    }
+    /// write `(params) => body`, or `(params) => { body }` if block is true.
+    /// params are always parenthesized so destructuring and rest params work.
+    fn gen_arrow<P, F>(&mut self, params: P, block: bool, body: F) -> Output
+    where
+        P: IntoIterator<Item = Js<'a>>,
+        F: FnOnce(&mut Self) -> Output,
+    {
         self.write_str("(")?;
-        self.gen_list(normalized_params)?;
+        self.gen_list(params)?;
+        if !block {
+            self.write_str(") => ")?;
+            return body(self);
+        }
         self.write_str(") => {")?;
         self.indent()?;
-        if need_return {
-            self.write_str("return ")?;
-        }
-        self.generate_ir(body)?;
+        body(self)?;
         self.deindent()?;
         self.write_str("}")
     }
@@ -608,6 +844,118 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         }
         Ok(())
     }
+    /// gen_list, but one item per line if the list and its closing bracket
+    /// don't fit the current line.
+    fn gen_wrapped_list(&mut self, exprs: Vec<Js<'a>>) -> Output {
+        if !self.exceeds_line(&exprs) {
+            return self.gen_list(exprs);
+        }
+        self.indent()?;
+        let len = exprs.len();
+        for (i, e) in exprs.into_iter().enumerate() {
+            self.generate_js_expr(e)?;
+            if i + 1 < len {
+                self.write_str(",")?;
+                self.newline()?;
+            }
+        }
+        self.deindent()
+    }
+    fn exceeds_line(&self, exprs: &[Js<'a>]) -> bool {
+        let option = &self.option;
+        if option.compact || option.max_line_length == 0 || exprs.is_empty() {
+            return false;
+        }
+        // 1 for the closing bracket
+        let mut meter = LineMeter {
+            width: self.column + 1,
+            limit: option.max_line_length,
+        };
+        // Err only means the measuring stopped early
+        let _ = self.measure_list(exprs, &mut meter);
+        meter.width > meter.limit
+    }
+    /// Mirror generate_js_expr's text up to the first line break.
+    /// Inner lists are measured unwrapped: if they fit, they are not wrapped.
+    fn measure_js(&self, expr: &Js<'a>, m: &mut LineMeter) -> Output {
+        match expr {
+            Js::Src(s) | Js::Param(s) => m.write_str(s),
+            Js::Num(n) => write!(m, "{}", n),
+            Js::StrLit(l) => {
+                let mut l = *l;
+                l.be_js_str().write_to(m)
+            }
+            Js::Simple(e, _) => e.write_to(m),
+            Js::Symbol(s) => {
+                m.write_str("_")?;
+                m.write_str(self.helper_str(*s))
+            }
+            // non-empty props always break the line after `{`
+            Js::Props(p) if p.is_empty() => m.write_str("{}"),
+            Js::Props(_) => {
+                m.write_str("{")?;
+                Err(fmt::Error)
+            }
+            Js::Compound(v) => v.iter().try_for_each(|e| self.measure_js(e, m)),
+            Js::Array(a) => {
+                m.write_str("[")?;
+                self.measure_list(a, m)?;
+                m.write_str("]")
+            }
+            Js::Call(c, args) => {
+                m.write_str("_")?;
+                m.write_str(self.helper_str(*c))?;
+                m.write_str("(")?;
+                self.measure_list(args, m)?;
+                m.write_str(")")
+            }
+            Js::FuncSimple { src, cache, .. } => {
+                let ty = get_handler_type(*src);
+                self.measure_handler(ty, *cache, m, |m| src.write_to(m))
+            }
+            Js::FuncCompound { body, ty, cache } => {
+                let ty = ty.clone();
+                self.measure_handler(ty, *cache, m, |m| {
+                    body.iter().try_for_each(|e| self.measure_js(e, m))
+                })
+            }
+        }
+    }
+    fn measure_list(&self, exprs: &[Js<'a>], m: &mut LineMeter) -> Output {
+        for (i, e) in exprs.iter().enumerate() {
+            if i > 0 {
+                m.write_str(", ")?;
+            }
+            self.measure_js(e, m)?;
+        }
+        Ok(())
+    }
+    /// same text as gen_handler
+    fn measure_handler<F>(&self, ty: HandlerType, cache: bool, m: &mut LineMeter, f: F) -> Output
+    where
+        F: FnOnce(&mut LineMeter) -> Output,
+    {
+        if cache {
+            write!(m, "_cache[{}] || (", self.cache_count)?;
+        }
+        match ty {
+            HandlerType::MemberExpr if cache => {
+                m.write_str("(...args) => ")?;
+                f(m)?;
+                m.write_str("?.(...args)")?;
+            }
+            HandlerType::InlineStmt => {
+                m.write_str("$event => (")?;
+                f(m)?;
+                m.write_str(")")?;
+            }
+            _ => f(m)?,
+        }
+        if cache {
+            m.write_str(")")?;
+        }
+        Ok(())
+    }
     fn gen_obj_props<V, P, K>(&mut self, props: P, cont: K) -> Output
     where
         P: IntoIterator<Item = (Js<'a>, V)>,
@@ -632,9 +980,9 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
     fn gen_obj_key(&mut self, key: Js<'a>) -> Output {
         if let Js::StrLit(mut k) = key {
             if is_simple_identifier(k) {
-                k.write_to(&mut self.writer)
+                k.write_to(&mut *self)
             } else {
-                k.be_js_str().write_to(&mut self.writer)
+                k.be_js_str().write_to(&mut *self)
             }
         } else {
             self.write_str("[")?;
@@ -655,6 +1003,80 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.generate_js_expr(dir_arr)?;
         self.write_str(")")
     }
+    fn gen_if_branches(
+        &mut self,
+        mut branches: vec::IntoIter<IfBranch<BaseConvertInfo<'a>>>,
+    ) -> Output {
+        let branch = match branches.next() {
+            Some(branch) => branch,
+            None => return self.gen_if_fallback(),
+        };
+        let child = *branch.child;
+        match branch.condition {
+            Some(condition) => self.gen_conditional(
+                condition,
+                |gen| gen.generate_ir(child),
+                |gen| gen.gen_if_branches(branches),
+            ),
+            None => self.generate_ir(child),
+        }
+    }
+    fn gen_if_fallback(&mut self) -> Output {
+        if !self.in_alterable {
+            // generate default v-else comment
+            let s = if self.option.is_dev { "v-if" } else { "" };
+            self.gen_comment_vnode(Js::str_lit(s), true)
+        } else {
+            // generate undefined for alterable_slots
+            self.write_str("undefined")
+        }
+    }
+    /// dev only hint for devtools, e.g. `render.__file = "Foo.vue"`
+    fn gen_file_name(&mut self) -> Output {
+        let option = &self.option;
+        let is_module = matches!(option.mode, ScriptMode::Module { .. });
+        let info = self.sfc_info;
+        let name = &info.self_name;
+        let render_name = match self.target().render_name() {
+            Some(name) if !option.body_only => name,
+            _ => return Ok(()),
+        };
+        if !option.is_dev || !option.emit_file_name || !is_module {
+            return Ok(());
+        }
+        if name.is_empty() {
+            return Ok(());
+        }
+        self.newline()?;
+        write!(self, "{}.__file = ", render_name)?;
+        VStr::raw(name).be_js_str().write_to(&mut *self)
+    }
+    /// createCommentVNode(text, asBlock). Structural placeholders like
+    /// the missing v-else branch pass `true` so the comment opens a block.
+    fn gen_comment_vnode(&mut self, text: Js<'a>, as_block: bool) -> Output {
+        let mut args = vec![text];
+        if as_block {
+            args.push(Js::Src("true"));
+        }
+        self.generate_js_expr(Js::Call(RH::CREATE_COMMENT, args))
+    }
+    /// write `(cond)\n? consequent\n: alternate` with branches indented
+    fn gen_conditional<C, A>(&mut self, cond: Js<'a>, consequent: C, alternate: A) -> Output
+    where
+        C: FnOnce(&mut Self) -> Output,
+        A: FnOnce(&mut Self) -> Output,
+    {
+        self.write_str("(")?;
+        self.generate_js_expr(cond)?;
+        self.write_str(")")?;
+        self.indent()?;
+        self.write_str("? ")?;
+        consequent(self)?;
+        self.newline()?;
+        self.write_str(": ")?;
+        alternate(self)?;
+        self.flush_deindent(1)
+    }
     fn gen_vnode_with_block(&mut self, v: BaseVNode<'a>) -> Output {
         if !v.is_block {
             return gen_vnode_real(self, v);
@@ -676,7 +1098,47 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.write_str(")")
     }
 
+    /// hint the element's template line, e.g. `/* line 2 */`
+    fn gen_line_comment(&mut self, loc: &SourceLocation) -> Output {
+        // synthesized vnode has no template location
+        if !self.option.line_comments || !self.option.is_dev || loc.end.offset == 0 {
+            return Ok(());
+        }
+        write!(self, "/* line {} */ ", loc.start.line)
+    }
+    /// `/* dynamic children: 0, 2 */` lists children tracked by the block
+    fn gen_dynamic_children_comment(&mut self, children: &[BaseIR<'a>]) -> Output {
+        if !self.option.dynamic_children_comments || !self.option.is_dev {
+            return Ok(());
+        }
+        let mut indices = children
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| is_dynamic_child(c))
+            .map(|(i, _)| i);
+        let first = match indices.next() {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        write!(self, "/* dynamic children: {}", first)?;
+        for i in indices {
+            write!(self, ", {}", i)?;
+        }
+        self.write_str(" */ ")
+    }
+    /// `// name` on its own line, line comment would swallow compact output
+    fn gen_section_comment(&mut self, name: &str) -> Output {
+        let option = &self.option;
+        if !option.section_comments || !option.is_dev || option.compact {
+            return Ok(());
+        }
+        write!(self, "// {}", name)?;
+        self.newline()
+    }
     fn newline(&mut self) -> Output {
+        if self.option.compact {
+            return Ok(());
+        }
         self.write_str("\n")?;
         // TODO: use exponential adding + lazy static
         for _ in 0..self.indent_level {
@@ -684,6 +1146,13 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         }
         Ok(())
     }
+    /// newline after a statement, compact mode relies on explicit semicolon
+    fn end_stmt(&mut self) -> Output {
+        if self.option.compact {
+            self.write_str(";")?;
+        }
+        self.newline()
+    }
     fn indent(&mut self) -> Output {
         self.indent_level += 1;
         self.newline()
@@ -704,21 +1173,38 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
 
     #[inline(always)]
     fn write_str(&mut self, s: &str) -> Output {
+        match s.rfind('\n') {
+            Some(i) => self.column = s.len() - i - 1,
+            None => self.column += s.len(),
+        }
         self.writer.write_str(s)
     }
 
     #[inline(always)]
     fn write_helper(&mut self, h: RH) -> Output {
-        debug_assert!(self.helpers.contains(h));
+        if self.option.check_helpers && !self.helpers.contains(h) {
+            self.missing_helpers.collect(h);
+        }
+        debug_assert!(self.option.check_helpers || self.helpers.contains(h));
         self.write_str("_")?;
-        self.write_str(h.helper_str(self.option.helper_strs))
+        self.write_str(self.helper_str(h))
+    }
+    fn helper_str(&self, h: RH) -> &'static str {
+        let option = &self.option;
+        (option.helper_name_override)(h).unwrap_or_else(|| h.helper_str(option.helper_strs))
     }
     #[inline(always)]
     fn write_patch(&mut self, flag: PatchFlag) -> Output {
-        if self.option.is_dev {
-            write!(self.writer, "{} /*{:?}*/", flag.bits(), flag)
+        if !self.option.is_dev {
+            return write!(self, "{}", flag.bits());
+        }
+        // special flags are negative, they must not be printed as bit union
+        if flag == PatchFlag::HOISTED {
+            write!(self, "{} /*HOISTED*/", flag.bits())
+        } else if flag == PatchFlag::BAIL {
+            write!(self, "{} /*BAIL*/", flag.bits())
         } else {
-            write!(self.writer, "{}", flag.bits())
+            write!(self, "{} /*{:?}*/", flag.bits(), flag)
         }
     }
 }
@@ -730,21 +1216,24 @@ fn gen_handler<'a, T, F>(
     func: F,
 ) -> Output
 where
-    T: ioWrite,
+    T: Write,
     F: FnOnce(&mut CodeWriter<'a, T>) -> Output,
 {
     if cache {
-        write!(gen.writer, "_cache[{}] || (", gen.cache_count)?;
+        let idx = gen.cache_count;
+        write!(gen, "_cache[{}] || (", idx)?;
     }
     match ty {
         HandlerType::FuncExpr => func(gen)?,
         HandlerType::MemberExpr => {
             if cache {
-                gen.write_str("(...args) => ")?;
-            }
-            func(gen)?;
-            if cache {
-                gen.write_str("?.(...args)")?;
+                let args = iter::once(Js::Src("...args"));
+                gen.gen_arrow(args, false, |gen| {
+                    func(gen)?;
+                    gen.write_str("?.(...args)")
+                })?;
+            } else {
+                func(gen)?;
             }
         }
         HandlerType::InlineStmt => {
@@ -760,7 +1249,7 @@ where
     Ok(())
 }
 
-fn gen_vnode_real<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, v: BaseVNode<'a>) -> Output {
+fn gen_vnode_real<'a, T: Write>(gen: &mut CodeWriter<'a, T>, v: BaseVNode<'a>) -> Output {
     let call_helper = get_vnode_call_helper(&v);
     gen.write_helper(call_helper)?;
     gen.write_str("(")?;
@@ -811,26 +1300,55 @@ macro_rules! gen_vnode_args {
 // TODO: unit test this monster
 /// Generate variadic vnode call argument list separated by comma.
 /// VNode arg is a heterogeneous list we need hard code the generation.
-fn gen_vnode_call_args<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, v: BaseVNode<'a>) -> Output {
+fn gen_vnode_call_args<'a, T: Write>(gen: &mut CodeWriter<'a, T>, v: BaseVNode<'a>) -> Output {
     let VNodeIR {
         tag,
         props,
         children,
         patch_flag,
         dynamic_props,
+        is_block,
+        location,
         ..
     } = v;
+    // empty props object is written as null for runtime fast path
+    let props = props.filter(|p| !matches!(p, Js::Props(ps) if ps.is_empty()));
+    // props win if v-html/v-text overrides element children
+    let children = if has_content_prop(props.as_ref()) {
+        if !children.is_empty() && gen.option.is_dev {
+            gen.warnings.push(CodegenWarning {
+                msg: "Element children are overridden by innerHTML/textContent.",
+                location: location.clone(),
+            });
+        }
+        vec![]
+    } else if is_void_element(gen, &tag) {
+        if !children.is_empty() && gen.option.is_dev {
+            gen.warnings.push(CodegenWarning {
+                msg: "Void element cannot have children. Children are dropped.",
+                location,
+            });
+        }
+        vec![]
+    } else {
+        children
+    };
 
     gen_vnode_args!(
         gen,
         true, { gen.generate_js_expr(tag)?; }
         props.is_some(), { gen.generate_js_expr(props.unwrap())?; }
-        !children.is_empty(), { gen.generate_children(children)?; }
+        !children.is_empty(), {
+            if is_block {
+                gen.gen_dynamic_children_comment(&children)?;
+            }
+            gen.generate_children(children)?;
+        }
         patch_flag != PatchFlag::empty(), {
             gen.write_patch(patch_flag)?;
         }
         !dynamic_props.is_empty(), {
-            let dps = dynamic_props.into_iter().map(Js::StrLit);
+            let dps = stringify_dynamic_prop_names(dynamic_props);
             gen.write_str("[")?;
             gen.gen_list(dps)?;
             gen.write_str("]")?;
@@ -839,7 +1357,45 @@ fn gen_vnode_call_args<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, v: BaseVNode
     Ok(())
 }
 
-fn gen_v_for_args<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, f: BaseFor<'a>) -> Output {
+/// runtime collects these children into the block's dynamicChildren
+fn is_dynamic_child(child: &BaseIR) -> bool {
+    match child {
+        IRNode::VNodeCall(v) => v.is_block || v.is_component || v.patch_flag.bits() > 0,
+        IRNode::TextCall(t) => t.need_patch,
+        IRNode::If(_) | IRNode::For(_) | IRNode::RenderSlotCall(_) => true,
+        _ => false,
+    }
+}
+
+/// dynamic props are collected in a hash set. Sort them for stable output.
+fn stringify_dynamic_prop_names<'a, I>(dynamic_props: I) -> impl Iterator<Item = Js<'a>>
+where
+    I: IntoIterator<Item = VStr<'a>>,
+{
+    let mut names: Vec<_> = dynamic_props.into_iter().collect();
+    names.sort_by_key(|n| n.raw);
+    names.into_iter().map(Js::StrLit)
+}
+
+fn is_void_element<T: Write>(gen: &CodeWriter<T>, tag: &Js) -> bool {
+    match tag {
+        Js::StrLit(t) => (gen.option.is_void_tag)(t.raw),
+        _ => false,
+    }
+}
+
+fn has_content_prop(props: Option<&Js>) -> bool {
+    let ps = match props {
+        Some(Js::Props(ps)) => ps,
+        _ => return false,
+    };
+    ps.iter().any(|(k, _)| match k {
+        Js::StrLit(k) => k.raw == "innerHTML" || k.raw == "textContent",
+        _ => false,
+    })
+}
+
+fn gen_v_for_args<'a, T: Write>(gen: &mut CodeWriter<'a, T>, f: BaseFor<'a>) -> Output {
     let flag = f.fragment_flag;
     gen_vnode_args!(
         gen,
@@ -847,13 +1403,13 @@ fn gen_v_for_args<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, f: BaseFor<'a>) -
         false, {  }
         true, { gen.generate_render_list(f)?; }
         true, {
-            write!(gen.writer, "{} /*{:?}*/", flag.bits(), flag)?;
+            write!(gen, "{} /*{:?}*/", flag.bits(), flag)?;
         }
     );
     Ok(())
 }
 
-fn gen_render_slot_args<'a, T: ioWrite>(
+fn gen_render_slot_args<'a, T: Write>(
     gen: &mut CodeWriter<'a, T>,
     r: BaseRenderSlot<'a>,
 ) -> Output {
@@ -876,8 +1432,7 @@ fn gen_render_slot_args<'a, T: ioWrite>(
     }
     if !fallbacks.is_empty() {
         gen.write_str(", ")?;
-        gen.write_str("() => ")?;
-        gen.generate_children(fallbacks)?;
+        gen.gen_arrow(iter::empty(), false, |gen| gen.generate_children(fallbacks))?;
     } else if no_slotted {
         gen.write_str(", ")?;
         gen.write_str("undefined")?;
@@ -894,40 +1449,38 @@ enum Slot<'a> {
     SlotFn(Option<Js<'a>>, Vec<BaseIR<'a>>),
     Flag(SlotFlag),
 }
-fn gen_stable_slot_fn<'a, T: ioWrite>(gen: &mut CodeWriter<'a, T>, slot: Slot<'a>) -> Output {
+fn gen_stable_slot_fn<'a, T: Write>(gen: &mut CodeWriter<'a, T>, slot: Slot<'a>) -> Output {
     match slot {
         Slot::SlotFn(param, body) => gen_slot_fn(gen, (param, body)),
         Slot::Flag(flag) => {
-            write!(gen.writer, "{} /*{:?}*/", flag as u8, flag)
+            write!(gen, "{} /*{:?}*/", flag as u8, flag)
         }
     }
 }
-fn gen_slot_fn<'a, T: ioWrite>(
+fn gen_slot_fn<'a, T: Write>(
     gen: &mut CodeWriter<'a, T>,
     (param, body): (Option<Js<'a>>, Vec<BaseIR<'a>>),
 ) -> Output {
     gen.write_helper(RH::WITH_CTX)?;
     gen.write_str("(")?;
-    gen.write_str("(")?;
-    if let Some(p) = param {
-        gen.generate_js_expr(p)?;
-    }
-    gen.write_str(") => [")?;
-    gen.indent()?;
-    let mut body = body.into_iter();
-    if let Some(b) = body.next() {
-        gen.generate_ir(b)?;
-    }
-    for b in body {
-        gen.write_str(", ")?;
-        gen.newline()?;
-        gen.generate_ir(b)?;
-    }
-    gen.deindent()?;
-    gen.write_str("]")?;
+    gen.gen_arrow(param, false, |gen| {
+        gen.write_str("[")?;
+        gen.indent()?;
+        let mut body = body.into_iter();
+        if let Some(b) = body.next() {
+            gen.generate_ir(b)?;
+        }
+        for b in body {
+            gen.write_str(", ")?;
+            gen.newline()?;
+            gen.generate_ir(b)?;
+        }
+        gen.deindent()?;
+        gen.write_str("]")
+    })?;
     gen.write_str(")")
 }
-fn gen_assets<'a, T: ioWrite>(
+fn gen_assets<'a, T: Write>(
     gen: &mut CodeWriter<'a, T>,
     assets: impl Iterator<Item = VStr<'a>>,
     resolver: RH,
@@ -939,19 +1492,19 @@ fn gen_assets<'a, T: ioWrite>(
             ""
         };
         gen.write_str("const ")?;
-        asset.write_to(&mut gen.writer)?;
+        asset.write_to(&mut *gen)?;
         gen.write_str(" = ")?;
         gen.write_helper(resolver)?;
         gen.write_str("(")?;
-        let raw = if resolver == RH::RESOLVE_COMPONENT {
+        let mut raw = if resolver == RH::RESOLVE_COMPONENT {
             *asset.clone().unbe_component()
         } else {
             *asset.clone().unbe_directive()
         };
-        raw.write_to(&mut gen.writer)?;
+        raw.be_js_str().write_to(&mut *gen)?;
         gen.write_str(hint)?;
         gen.write_str(")")?;
-        gen.newline()?;
+        gen.end_stmt()?;
     }
     Ok(())
 }
@@ -980,19 +1533,28 @@ fn runtime_dirs_to_js_arr(dirs: Vec<RuntimeDir<BaseConvertInfo>>) -> Js {
 mod test {
     use crate::converter::test::{base_convert, handler_convert};
     use super::*;
+    use crate::codegen::ExportStyle;
     use crate::cast;
     use crate::{BindingMetadata, BindingTypes};
     fn gen<'a>(mut ir: BaseRoot<'a>, info: &'a SFCInfo<'a>) -> String {
         ir.top_scope.helpers.ignore_missing();
-        let mut writer = CodeWriter::new(vec![], Default::default(), info);
+        let mut writer = CodeWriter::new(String::new(), Default::default(), info);
         writer.generate_root(ir).unwrap();
-        String::from_utf8(writer.writer.inner).unwrap()
+        writer.writer
     }
     fn base_gen(s: &str) -> String {
         let ir = base_convert(s);
         let info = SFCInfo::default();
         gen(ir, &info)
     }
+    fn gen_with_option(s: &str, option: CodeGenerateOption) -> String {
+        let mut ir = base_convert(s);
+        ir.top_scope.helpers.ignore_missing();
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        writer.writer
+    }
     #[test]
     fn test_text() {
         let s = base_gen("hello       world");
@@ -1038,6 +1600,31 @@ mod test {
         assert!(s.contains("openBlock"), "{}", s);
     }
     #[test]
+    fn test_special_patch_flag() {
+        let mut ir = base_convert("<p/>");
+        let vn = cast!(&mut ir.body[0], IRNode::VNodeCall);
+        vn.patch_flag = PatchFlag::HOISTED;
+        let s = gen(ir, &SFCInfo::default());
+        assert!(s.contains(r#"("p", null, null, -1 /*HOISTED*/)"#), "{}", s);
+        let mut ir = base_convert("<p/>");
+        let vn = cast!(&mut ir.body[0], IRNode::VNodeCall);
+        vn.patch_flag = PatchFlag::BAIL;
+        let s = gen(ir, &SFCInfo::default());
+        assert!(s.contains(r#"("p", null, null, -2 /*BAIL*/)"#), "{}", s);
+        let option = CodeGenerateOption {
+            is_dev: false,
+            ..Default::default()
+        };
+        let mut ir = base_convert("<p/>");
+        let vn = cast!(&mut ir.body[0], IRNode::VNodeCall);
+        vn.patch_flag = PatchFlag::HOISTED;
+        ir.top_scope.helpers.ignore_missing();
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        assert!(writer.writer.contains(r#"("p", null, null, -1)"#));
+    }
+    #[test]
     fn test_attr() {
         let s = base_gen("<p class='test' id='id'/>");
         assert!(s.contains("\"p\""), "{}", s);
@@ -1047,6 +1634,81 @@ mod test {
         assert!(s.contains(r#""aria-label": "close""#), "{}", s);
     }
     #[test]
+    fn test_boolean_attr() {
+        let s = base_gen("<button disabled/>");
+        assert!(s.contains(r#"disabled: """#), "{}", s);
+        let s = base_gen("<button :disabled='flag'/>");
+        assert!(s.contains("disabled: flag"), "{}", s);
+        assert!(!s.contains(r#"disabled: """#), "{}", s);
+    }
+    #[test]
+    fn test_literal_prop() {
+        let s = base_gen("<p :count='5' :flag='true' :obj='{ a: 1 }'/>");
+        assert!(s.contains("count: 5,"), "{}", s);
+        assert!(s.contains("flag: true,"), "{}", s);
+        assert!(s.contains("obj: { a: 1 },"), "{}", s);
+    }
+    #[test]
+    fn test_content_prop_drops_children() {
+        // v-html is converted to innerHTML prop in dom build
+        let s = base_gen("<div :innerHTML='x'>ignored</div>");
+        assert!(s.contains("innerHTML: x"), "{}", s);
+        assert!(!s.contains("ignored"), "{}", s);
+        let s = base_gen("<div :textContent='x'><p/></div>");
+        assert!(!s.contains("\"p\""), "{}", s);
+    }
+    #[test]
+    fn test_content_prop_warning() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<div :innerHTML='x'>ignored</div>");
+        ir.top_scope.helpers.ignore_missing();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.generate_root(ir).unwrap();
+        let result = writer.take_result();
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].msg.contains("innerHTML"));
+        let location = &result.warnings[0].location;
+        assert_eq!(location.start.offset, 0);
+        assert_eq!(location.end.offset, 33);
+        // no warning without children
+        let mut ir = base_convert("<div :innerHTML='x'/>");
+        ir.top_scope.helpers.ignore_missing();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.generate_root(ir).unwrap();
+        assert!(writer.take_result().warnings.is_empty());
+    }
+    #[test]
+    fn test_component_slots_object() {
+        let s = base_gen("<comp>{{x}}</comp>");
+        assert!(s.contains("_component_comp, null, {\n"), "{}", s);
+        assert!(s.contains("default: _withCtx(() => ["), "{}", s);
+        assert!(s.contains("_: 1 /*Stable*/,\n    })"), "{}", s);
+        assert!(!s.contains("null, ["), "{}", s);
+        // element children are still an array
+        let s = base_gen("<div><p/></div>");
+        assert!(s.contains("\"div\", null, ["), "{}", s);
+    }
+    #[test]
+    fn test_void_element() {
+        let option = || CodeGenerateOption {
+            is_void_tag: |t| t == "img",
+            ..Default::default()
+        };
+        let s = gen_with_option("<img src='x'/>", option());
+        assert!(s.contains("_createElementVNode(\"img\", {"), "{}", s);
+        assert!(s.ends_with("})\n  }\n}"), "{}", s);
+        // parser without void awareness nests content into img
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<img src='x'>ignored</img>");
+        ir.top_scope.helpers.ignore_missing();
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option()), &info);
+        writer.generate_root(ir).unwrap();
+        let result = writer.take_result();
+        assert!(!writer.writer.contains("ignored"), "{}", writer.writer);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].msg.contains("Void element"));
+    }
+    #[test]
     fn test_v_bind_shorthand() {
         let s = base_gen("<p :prop='id'/>");
         assert!(s.contains("prop: id"), "{}", s);
@@ -1087,6 +1749,56 @@ mod test {
         assert!(s.contains("openBlock"), "{}", s);
     }
     #[test]
+    fn test_v_if_component_block() {
+        let mut ir = base_convert("<comp v-if='condition'/>");
+        let i = cast!(&mut ir.body[0], IRNode::If);
+        let vn = cast!(&mut *i.branches[0].child, IRNode::VNodeCall);
+        assert!(vn.is_component);
+        vn.is_block = true;
+        let s = gen(ir, &SFCInfo::default());
+        assert!(
+            s.contains("(_openBlock(), _createBlock(_component_comp"),
+            "{}",
+            s
+        );
+        assert!(!s.contains("_createElementBlock"), "{}", s);
+        let mut ir = base_convert("<p v-if='condition'/>");
+        let i = cast!(&mut ir.body[0], IRNode::If);
+        let vn = cast!(&mut *i.branches[0].child, IRNode::VNodeCall);
+        vn.is_block = true;
+        let s = gen(ir, &SFCInfo::default());
+        assert!(
+            s.contains("(_openBlock(), _createElementBlock(\"p\""),
+            "{}",
+            s
+        );
+        assert!(!s.contains("_createBlock("), "{}", s);
+    }
+    #[test]
+    fn test_vnode_call_helper() {
+        let s = base_gen("<div/>");
+        assert!(s.contains("_createElementVNode(\"div\""), "{}", s);
+        assert!(!s.contains("_createVNode("), "{}", s);
+        let s = base_gen("<MyComp/>");
+        assert!(s.contains("_createVNode(_component_MyComp"), "{}", s);
+        assert!(!s.contains("_createElementVNode("), "{}", s);
+        let s = base_gen("<BaseTransition/>");
+        assert!(s.contains("_createVNode(_BaseTransition"), "{}", s);
+    }
+    #[test]
+    fn test_comment_vnode() {
+        let s = base_gen("<!-- x -->");
+        assert!(s.contains(r#"_createCommentVNode(" x ")"#), "{}", s);
+        let s = base_gen("<p v-if='a'/>");
+        assert!(s.contains(r#"_createCommentVNode("v-if", true)"#), "{}", s);
+        let option = CodeGenerateOption {
+            is_dev: false,
+            ..Default::default()
+        };
+        let s = gen_with_option("<p v-if='a'/>", option);
+        assert!(s.contains(r#"_createCommentVNode("", true)"#), "{}", s);
+    }
+    #[test]
     fn test_v_if_slot() {
         let s = base_gen("<slot v-if='condition'/>");
         assert!(!s.contains("openBlock"), "{}", s);
@@ -1199,6 +1911,15 @@ mod test {
         // func expr
         let s = gen_on("<p @click='() => a()'/>");
         assert!(s.contains("onClick: () => a()"), "{}", s);
+        // update expression is a statement, not a reference
+        let s = gen_on("<p @click='count++'/>");
+        assert!(s.contains("onClick: $event => (count++)"), "{}", s);
+        let s = gen_on("<p @click='fn'/>");
+        assert!(s.contains("onClick: fn"), "{}", s);
+        let s = gen_on("<p @click='e => fn(e)'/>");
+        assert!(s.contains("onClick: e => fn(e)"), "{}", s);
+        let s = gen_on("<p @click='function (e) { fn(e) }'/>");
+        assert!(s.contains("onClick: function (e) { fn(e) }"), "{}", s);
     }
 
     #[test]
@@ -1208,9 +1929,9 @@ mod test {
         let mut helpers = HelperCollector::new();
         helpers.collect(RH::WITH_DIRECTIVES);
         ir.top_scope.helpers = helpers;
-        let mut writer = CodeWriter::new(vec![], Default::default(), &info);
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
         writer.generate_root(ir).unwrap();
-        let s = String::from_utf8(writer.writer.inner).unwrap();
+        let s = writer.writer;
         assert!(s.contains("withDirectives: _withDirectives"), "{}", s);
     }
 
@@ -1227,11 +1948,524 @@ mod test {
             },
             ..Default::default()
         };
-        let mut writer = CodeWriter::new(vec![], Rc::new(option), &info);
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
         writer.generate_root(ir).unwrap();
-        let s = String::from_utf8(writer.writer.inner).unwrap();
+        let s = writer.writer;
         assert!(s.contains("import"), "{}", s);
         assert!(s.contains("createTextVNode as _createTextVNode"), "{}", s);
         assert!(s.contains("from \"vue\""), "{}", s);
     }
+
+    #[test]
+    fn test_custom_helper_import() {
+        const MY_HELPER: RH = RH(RH::INTERNAL_MAX);
+        let info = SFCInfo::default();
+        let mut ir = base_convert("test");
+        let mut helpers = HelperCollector::new();
+        helpers.collect(RH::CREATE_TEXT);
+        // a custom transform can collect it like builtin helpers
+        helpers.collect(MY_HELPER);
+        ir.top_scope.helpers = helpers;
+        let option = CodeGenerateOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            helper_strs: &["myHelper"],
+            helper_source: |rh| (rh == MY_HELPER).then(|| "my-runtime"),
+            ..Default::default()
+        };
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let s = writer.writer;
+        let vue_import = "{\n  createTextVNode as _createTextVNode, \n} from \"vue\"";
+        assert!(s.contains(vue_import), "{}", s);
+        let custom_import = "{\n  myHelper as _myHelper, \n} from \"my-runtime\"";
+        assert!(s.contains(custom_import), "{}", s);
+    }
+
+    #[test]
+    fn test_every_js_variant() {
+        assert_eq!(gen_js(Js::Src("a + b")), "a + b");
+        assert_eq!(gen_js(Js::Param("p")), "p");
+        assert_eq!(gen_js(Js::Num(3)), "3");
+        assert_eq!(gen_js(Js::str_lit("s")), r#""s""#);
+        assert_eq!(gen_js(Js::simple("x")), "x");
+        let prefixed = Js::simple(*VStr::raw("x").prefix_ctx());
+        assert_eq!(gen_js(prefixed), "_ctx.x");
+        assert_eq!(gen_js(Js::Symbol(RH::FRAGMENT)), "_Fragment");
+        let props = gen_js(Js::Props(vec![(Js::str_lit("a"), Js::simple("b"))]));
+        assert!(props.starts_with('{') && props.ends_with('}'), "{}", props);
+        assert!(props.contains("a: b"), "{}", props);
+        let compound = Js::Compound(vec![Js::Src("("), Js::simple("a"), Js::Src(")")]);
+        assert_eq!(gen_js(compound), "(a)");
+        assert_eq!(gen_js(Js::Array(vec![Js::Num(1), Js::Num(2)])), "[1, 2]");
+        let call = Js::Call(RH::TO_DISPLAY_STRING, vec![Js::simple("a")]);
+        assert_eq!(gen_js(call), "_toDisplayString(a)");
+        assert_eq!(gen_js(Js::func("a++")), "$event => (a++)");
+        let func = Js::FuncCompound {
+            body: vec![Js::Src("a"), Js::Src("()")],
+            ty: HandlerType::InlineStmt,
+            cache: false,
+        };
+        assert_eq!(gen_js(func), "$event => (a())");
+    }
+    #[test]
+    fn test_max_line_length() {
+        let gen_width = |e: Js, max_line_length| {
+            let info = SFCInfo::default();
+            let option = CodeGenerateOption {
+                max_line_length,
+                ..Default::default()
+            };
+            let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+            writer.helpers.ignore_missing();
+            writer.generate_js_expr(e).unwrap();
+            writer.writer
+        };
+        // `[aaaaaaaa, bbbbbbbb]` is exactly 20 wide
+        let arr = |a| Js::Array(vec![Js::Src(a), Js::Src("bbbbbbbb")]);
+        assert_eq!(gen_width(arr("aaaaaaaa"), 20), "[aaaaaaaa, bbbbbbbb]");
+        assert_eq!(
+            gen_width(arr("aaaaaaaaa"), 20),
+            "[\n  aaaaaaaaa,\n  bbbbbbbb\n]"
+        );
+        assert_eq!(gen_width(arr("aaaaaaaaa"), 0), "[aaaaaaaaa, bbbbbbbb]");
+        // text before the list on the same line counts
+        let prefixed = Js::Compound(vec![Js::Src("x = "), arr("aaaaaaaa")]);
+        assert_eq!(gen_width(prefixed, 20), "x = [\n  aaaaaaaa,\n  bbbbbbbb\n]");
+        // `_toDisplayString(aaa, b)` is exactly 24 wide
+        let call = |a| Js::Call(RH::TO_DISPLAY_STRING, vec![Js::Src(a), Js::Src("b")]);
+        assert_eq!(gen_width(call("aaa"), 24), "_toDisplayString(aaa, b)");
+        assert_eq!(
+            gen_width(call("aaaa"), 24),
+            "_toDisplayString(\n  aaaa,\n  b\n)"
+        );
+    }
+    #[test]
+    fn test_default_line_length() {
+        let max = CodeGenerateOption::default().max_line_length;
+        assert_eq!(max, 80);
+        // `[` + n items of `xxxxxxxx` + `, ` separators + `]`
+        let width = |n: usize| 2 + n * 8 + (n - 1) * 2;
+        let arr = |n| Js::Array(vec![Js::Src("xxxxxxxx"); n]);
+        let info = SFCInfo::default();
+        let gen = |e: Js, col: &'static str| {
+            let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+            writer.helpers.ignore_missing();
+            writer.write_str(col).unwrap();
+            writer.generate_js_expr(e).unwrap();
+            writer.writer
+        };
+        // 8 items are 80 wide: fits exactly
+        assert_eq!(width(8), max);
+        assert!(!gen(arr(8), "").contains('\n'));
+        // one more column pushes it over
+        assert!(gen(arr(8), " ").contains("[\n  xxxxxxxx,\n"));
+        assert!(gen(arr(9), "").contains("[\n  xxxxxxxx,\n"));
+    }
+    #[test]
+    fn test_hoisted_names() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<div/>");
+        ir.top_scope.helpers.ignore_missing();
+        for hoist in ["<p/>", "<span/>"].iter() {
+            let node = base_convert(hoist).body.pop().unwrap();
+            ir.top_scope.hoists.push(node);
+        }
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.generate_root(ir).unwrap();
+        let result = writer.take_result();
+        assert_eq!(result.hoisted_names, vec!["_hoisted_0", "_hoisted_1"]);
+        for name in result.hoisted_names.iter() {
+            let decl = format!("const {} = ", name);
+            assert!(writer.writer.contains(&decl), "{}", writer.writer);
+        }
+    }
+
+    #[test]
+    fn test_empty_props_as_null() {
+        let s = base_gen("<div><p/></div>");
+        assert!(s.contains(r#"_createElementVNode("p")"#), "{}", s);
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<div><p/></div>");
+        let div = cast!(&mut ir.body[0], IRNode::VNodeCall);
+        div.props = Some(Js::Props(vec![]));
+        let s = gen(ir, &info);
+        assert!(s.contains(r#""div", null, ["#), "{}", s);
+        assert!(!s.contains("{}"), "{}", s);
+    }
+
+    #[test]
+    fn test_cache_count() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<p @click='a'/><p @click='b'/><p v-once/><p/>");
+        ir.top_scope.helpers.ignore_missing();
+        for node in ir.body.iter_mut() {
+            let props = match node {
+                IRNode::VNodeCall(v) => &mut v.props,
+                _ => continue,
+            };
+            if let Some(Js::Props(ps)) = props {
+                for (_, v) in ps.iter_mut() {
+                    if let Js::FuncSimple { cache, .. } = v {
+                        *cache = true;
+                    }
+                }
+            }
+        }
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.generate_root(ir).unwrap();
+        let result = writer.take_result();
+        // two cached handlers and one v-once
+        assert_eq!(result.cache_count, 3, "{}", writer.writer);
+        assert!(writer.writer.contains("_cache[2]"), "{}", writer.writer);
+        let result = CodeWriter::new(String::new(), Default::default(), &info).take_result();
+        assert_eq!(result.cache_count, 0);
+    }
+
+    #[test]
+    fn test_section_comments() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<div/>");
+        ir.top_scope.helpers.ignore_missing();
+        let hoist = base_convert("<p/>").body.pop().unwrap();
+        ir.top_scope.hoists.push(hoist);
+        ir.top_scope
+            .components
+            .insert(*VStr::raw("comp").be_component());
+        let option = CodeGenerateOption {
+            section_comments: true,
+            ..Default::default()
+        };
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let s = writer.writer;
+        let hoists = s.find("// hoists\nconst _hoisted_0").expect(&s);
+        let assets = s.find("// assets\n    const _component_comp").expect(&s);
+        let ret = s.find("// return\n    return").expect(&s);
+        assert!(hoists < assets && assets < ret, "{}", s);
+        // off by default
+        let s = base_gen("<div/>");
+        assert!(!s.contains("//"), "{}", s);
+    }
+
+    #[test]
+    fn test_missing_helper_import() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<p>{{a}}</p>");
+        let mut helpers = HelperCollector::new();
+        // forget to collect TO_DISPLAY_STRING
+        helpers.collect(RH::CREATE_ELEMENT_VNODE);
+        ir.top_scope.helpers = helpers;
+        let option = CodeGenerateOption {
+            check_helpers: true,
+            ..Default::default()
+        };
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let result = writer.take_result();
+        let missing = CodegenError::MissingHelperImport(RH::TO_DISPLAY_STRING);
+        assert_eq!(result.errors, vec![missing]);
+    }
+
+    #[test]
+    fn test_helper_name_override() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<MyComp/>");
+        let mut helpers = HelperCollector::new();
+        helpers.collect(RH::CREATE_VNODE);
+        helpers.collect(RH::RESOLVE_COMPONENT);
+        ir.top_scope.helpers = helpers;
+        let comp = *VStr::raw("MyComp").be_component();
+        ir.top_scope.components.insert(comp);
+        let option = CodeGenerateOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "my-vue".into(),
+            },
+            helper_name_override: |rh| (rh == RH::CREATE_VNODE).then(|| "myCreateVNode"),
+            ..Default::default()
+        };
+        let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let s = writer.writer;
+        assert!(s.contains("myCreateVNode as _myCreateVNode, "), "{}", s);
+        assert!(
+            s.contains("return _myCreateVNode(_component_MyComp)"),
+            "{}",
+            s
+        );
+        assert!(
+            s.contains("resolveComponent as _resolveComponent, "),
+            "{}",
+            s
+        );
+        assert!(!s.contains("_createVNode"), "{}", s);
+    }
+
+    #[test]
+    fn test_elide_unused_params() {
+        fn elided_option() -> CodeGenerateOption {
+            CodeGenerateOption {
+                mode: ScriptMode::Function {
+                    prefix_identifier: true,
+                    runtime_global_name: "Vue".into(),
+                },
+                elide_unused_params: true,
+                ..Default::default()
+            }
+        }
+        fn gen_elided(s: &str) -> String {
+            gen_with_option(s, elided_option())
+        }
+        let s = gen_elided("<p>static</p>");
+        assert!(s.contains("function render() {"), "{}", s);
+        assert!(!s.contains("_cache"), "{}", s);
+        let s = gen_elided("<p v-once/>");
+        assert!(s.contains("function render(_ctx, _cache) {"), "{}", s);
+        // string literal mentioning a param does not keep it
+        let s = gen_elided("<p title='_ctx _cache'/>");
+        assert!(s.contains("function render() {"), "{}", s);
+        // cache slots used by the body are reported
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<p v-once/><p v-once/>");
+        ir.top_scope.helpers.ignore_missing();
+        let mut writer = CodeWriter::new(String::new(), Rc::new(elided_option()), &info);
+        writer.generate_root(ir).unwrap();
+        assert_eq!(writer.take_result().cache_count, 2, "{}", writer.writer);
+        // not elided without option
+        let s = base_gen("<p>static</p>");
+        assert!(s.contains("function render(_ctx, _cache) {"), "{}", s);
+    }
+    #[test]
+    fn test_gen_arrow() {
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        let params = vec![Js::Src("{ a, b }"), Js::Param("c")];
+        writer
+            .gen_arrow(params, false, |gen| gen.write_str("a + c"))
+            .unwrap();
+        assert_eq!(writer.writer, "({ a, b }, c) => a + c");
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer
+            .gen_arrow(iter::empty(), true, |gen| gen.write_str("return a"))
+            .unwrap();
+        assert_eq!(writer.writer, "() => {\n  return a\n}");
+    }
+    #[test]
+    fn test_gen_conditional() {
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer
+            .gen_conditional(
+                Js::Src("a"),
+                |gen| gen.write_str("b"),
+                |gen| gen.write_str("c"),
+            )
+            .unwrap();
+        assert_eq!(writer.writer, "(a)\n  ? b\n  : c");
+        assert_eq!(writer.indent_level, 0);
+    }
+
+    fn gen_js(expr: Js) -> String {
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.helpers.ignore_missing();
+        writer.generate_js_expr(expr).unwrap();
+        writer.writer
+    }
+
+    #[test]
+    fn test_array_of_props() {
+        let obj = Js::Props(vec![(Js::str_lit("a"), Js::simple("b"))]);
+        let arr = Js::Array(vec![obj, Js::str_lit("c")]);
+        let s = gen_js(arr);
+        assert_eq!(s, "[{\n  a: b,\n}, \"c\"]");
+        let arr = Js::Array(vec![Js::Props(vec![]), Js::Props(vec![])]);
+        let s = gen_js(arr);
+        assert_eq!(s, "[{}, {}]");
+    }
+
+    #[test]
+    fn test_array_spread() {
+        let children = Js::spread(Js::simple("dynamicChildren"));
+        let arr = Js::Array(vec![Js::simple("staticChild"), children]);
+        let s = gen_js(arr);
+        assert_eq!(s, "[staticChild, ...dynamicChildren]");
+    }
+
+    #[test]
+    fn test_optional_chaining() {
+        use crate::converter::{test::option_convert, ConvertOption};
+        let option = ConvertOption {
+            optional_chaining: true,
+            ..Default::default()
+        };
+        let ir = option_convert("{{a.b.c}} {{d}}", option);
+        let s = gen(ir, &SFCInfo::default());
+        assert!(s.contains("_toDisplayString(a?.b?.c)"), "{}", s);
+        assert!(s.contains("_toDisplayString(d)"), "{}", s);
+        let s = base_gen("{{a.b}}");
+        assert!(s.contains("_toDisplayString(a.b)"), "{}", s);
+    }
+
+    fn module_option(export_style: ExportStyle) -> CodeGenerateOption {
+        CodeGenerateOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            export_style,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cjs_mode() {
+        let option = CodeGenerateOption {
+            mode: ScriptMode::Cjs {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let s = gen_with_option("<p>{{a}}</p>", option);
+        assert!(s.starts_with("const {"), "{}", s);
+        assert!(s.contains("} = require(\"vue\")"), "{}", s);
+        assert!(s.contains("\nfunction render(_ctx, _cache) {"), "{}", s);
+        assert!(s.ends_with("}\nmodule.exports = { render }"), "{}", s);
+        assert!(!s.contains("import"), "{}", s);
+        assert!(!s.contains("export "), "{}", s);
+    }
+
+    #[test]
+    fn test_codegen_target() {
+        let option = |target, style| CodeGenerateOption {
+            target,
+            ..module_option(style)
+        };
+        let s = gen_with_option("<p/>", option(CodegenTarget::Dom, ExportStyle::Named));
+        assert!(
+            s.contains("export function render(_ctx, _cache) {"),
+            "{}",
+            s
+        );
+        // unnamed arrow is always the default export
+        for style in [ExportStyle::None, ExportStyle::Named, ExportStyle::Default] {
+            let s = gen_with_option("<p/>", option(CodegenTarget::Functional, style));
+            assert!(s.contains("\nexport default (_ctx, _cache) => {"), "{}", s);
+            assert!(!s.contains("function"), "{}", s);
+        }
+        let option = CodeGenerateOption {
+            target: CodegenTarget::Functional,
+            mode: ScriptMode::Cjs {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let s = gen_with_option("<p/>", option);
+        assert!(
+            s.contains("\nmodule.exports = (_ctx, _cache) => {"),
+            "{}",
+            s
+        );
+        assert!(!s.contains("module.exports = {"), "{}", s);
+        // inline SFC render is an arrow regardless of target
+        let info = SFCInfo {
+            inline: true,
+            ..Default::default()
+        };
+        let mut ir = base_convert("<p/>");
+        ir.top_scope.helpers.ignore_missing();
+        let option = Rc::new(module_option(ExportStyle::None));
+        let mut writer = CodeWriter::new(String::new(), option, &info);
+        writer.generate_root(ir).unwrap();
+        let s = writer.writer;
+        assert!(s.contains("\nexport default (_ctx, _cache) => {"), "{}", s);
+    }
+
+    #[test]
+    fn test_export_style() {
+        let s = gen_with_option("test", module_option(ExportStyle::None));
+        assert!(s.contains("function render("), "{}", s);
+        assert!(!s.contains("export"), "{}", s);
+        let s = gen_with_option("test", module_option(ExportStyle::Named));
+        assert!(s.contains("export function render("), "{}", s);
+        let s = gen_with_option("test", module_option(ExportStyle::Default));
+        assert!(s.contains("export default function render("), "{}", s);
+        // function mode ignores export style
+        let option = CodeGenerateOption {
+            export_style: ExportStyle::Default,
+            ..Default::default()
+        };
+        let s = gen_with_option("test", option);
+        assert!(!s.contains("export"), "{}", s);
+    }
+
+    #[test]
+    fn test_no_side_effects() {
+        let option = |style| CodeGenerateOption {
+            no_side_effects: true,
+            ..module_option(style)
+        };
+        let s = gen_with_option("test", option(ExportStyle::Named));
+        assert!(
+            s.contains("\n/* @__NO_SIDE_EFFECTS__ */ export function render("),
+            "{}",
+            s
+        );
+        let s = gen_with_option("test", option(ExportStyle::None));
+        assert!(
+            s.contains("\n/* @__NO_SIDE_EFFECTS__ */ function render("),
+            "{}",
+            s
+        );
+        let s = gen_with_option("test", module_option(ExportStyle::Named));
+        assert!(!s.contains("@__NO_SIDE_EFFECTS__"), "{}", s);
+        // function mode returns render, there is no declaration to annotate
+        let option = CodeGenerateOption {
+            no_side_effects: true,
+            ..Default::default()
+        };
+        let s = gen_with_option("test", option);
+        assert!(!s.contains("@__NO_SIDE_EFFECTS__"), "{}", s);
+    }
+
+    #[test]
+    fn test_emit_file_name() {
+        let info = SFCInfo {
+            self_name: "Foo.vue".into(),
+            ..Default::default()
+        };
+        let gen_file = |option: CodeGenerateOption| {
+            let mut ir = base_convert("test");
+            ir.top_scope.helpers.ignore_missing();
+            let mut writer = CodeWriter::new(String::new(), Rc::new(option), &info);
+            writer.generate_root(ir).unwrap();
+            writer.writer
+        };
+        let option = CodeGenerateOption {
+            emit_file_name: true,
+            ..module_option(ExportStyle::Default)
+        };
+        let s = gen_file(option);
+        assert!(s.ends_with("}\nrender.__file = \"Foo.vue\""), "{}", s);
+        let option = CodeGenerateOption {
+            emit_file_name: true,
+            is_dev: false,
+            ..module_option(ExportStyle::Default)
+        };
+        assert!(!gen_file(option).contains("__file"));
+        assert!(!gen_file(module_option(ExportStyle::Default)).contains("__file"));
+    }
+
+    #[test]
+    fn test_body_only() {
+        let option = CodeGenerateOption {
+            body_only: true,
+            ..Default::default()
+        };
+        let s = gen_with_option("<p>{{a}}</p>", option);
+        assert!(s.starts_with("return "), "{}", s);
+        assert!(!s.contains("function"), "{}", s);
+        assert!(!s.contains("with (_ctx)"), "{}", s);
+        assert!(s.contains("_createElementVNode(\"p\""), "{}", s);
+    }
 }
@@ -0,0 +1,208 @@
+//! Approximate output size without generating code.
+//! The numbers are rough guesses of typical output, good for buffer
+//! capacity or quick metrics, not for exact accounting.
+use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot};
+use crate::ir::{CacheKind, IRNode, JsExpr as Js, Slot};
+
+/// e.g. `_createElementVNode(` plus closing paren
+const HELPER_CALL: usize = 24;
+/// function signature, return and closing brackets
+const PROLOGUE: usize = 60;
+/// patch flag with dev comment, e.g. `, 1 /*TEXT*/`
+const PATCH_FLAG: usize = 12;
+/// separator plus indentation per list item
+const ITEM: usize = 6;
+
+pub fn estimate_size(root: &BaseRoot) -> usize {
+    let top = &root.top_scope;
+    // helper is written twice in import and declaration
+    let helpers = top.helpers.clone().into_iter().count() * HELPER_CALL * 2;
+    let assets = top
+        .components
+        .iter()
+        .chain(top.directives.iter())
+        .map(|a| a.raw.len() * 2 + HELPER_CALL * 2)
+        .sum::<usize>();
+    let hoists = top
+        .hoists
+        .iter()
+        .map(|h| estimate_ir(h) + HELPER_CALL)
+        .sum::<usize>();
+    let classes = top
+        .classes
+        .iter()
+        .map(|c| estimate_js(c) + HELPER_CALL)
+        .sum::<usize>();
+    let body = estimate_children(&root.body);
+    PROLOGUE + helpers + assets + hoists + classes + body
+}
+
+fn estimate_children(children: &[BaseIR]) -> usize {
+    children.iter().map(|c| estimate_ir(c) + ITEM).sum()
+}
+
+fn estimate_ir(ir: &BaseIR) -> usize {
+    use IRNode as IR;
+    match ir {
+        IR::TextCall(t) => {
+            let texts = t.texts.iter().map(estimate_js).sum::<usize>();
+            if t.fast_path {
+                texts
+            } else {
+                texts + HELPER_CALL + PATCH_FLAG
+            }
+        }
+        IR::If(i) => {
+            i.branches
+                .iter()
+                .map(|b| {
+                    let cond = b.condition.as_ref().map_or(0, estimate_js);
+                    cond + estimate_ir(&b.child) + ITEM * 2
+                })
+                // placeholder comment for missing v-else
+                .sum::<usize>()
+                + HELPER_CALL
+        }
+        IR::For(f) => {
+            let p = &f.parse_result;
+            let params = [Some(&p.value), p.key.as_ref(), p.index.as_ref()]
+                .iter()
+                .flatten()
+                .map(|j| estimate_js(j) + 2)
+                .sum::<usize>();
+            // openBlock, createElementBlock and renderList
+            let source = estimate_js(&f.source);
+            HELPER_CALL * 3 + PATCH_FLAG + source + params + estimate_ir(&f.child)
+        }
+        IR::VNodeCall(v) => {
+            let block = if v.is_block { HELPER_CALL } else { 0 };
+            let props = v.props.as_ref().map_or(0, estimate_js);
+            let dirs = v
+                .directives
+                .iter()
+                .map(|d| {
+                    let exprs = [
+                        Some(&d.name),
+                        d.expr.as_ref(),
+                        d.arg.as_ref(),
+                        d.mods.as_ref(),
+                    ];
+                    exprs
+                        .iter()
+                        .flatten()
+                        .map(|j| estimate_js(j) + 2)
+                        .sum::<usize>()
+                        + ITEM
+                })
+                .sum::<usize>();
+            let dirs = if dirs > 0 { dirs + HELPER_CALL } else { 0 };
+            HELPER_CALL
+                + block
+                + estimate_js(&v.tag)
+                + props
+                + estimate_children(&v.children)
+                + dirs
+                + PATCH_FLAG
+        }
+        IR::RenderSlotCall(r) => {
+            let exprs = [Some(&r.slot_obj), Some(&r.slot_name), r.slot_props.as_ref()];
+            let args = exprs
+                .iter()
+                .flatten()
+                .map(|j| estimate_js(j) + 2)
+                .sum::<usize>();
+            HELPER_CALL + args + estimate_children(&r.fallbacks)
+        }
+        IR::VSlotUse(s) => {
+            let stable = s.stable_slots.iter().map(estimate_slot).sum::<usize>();
+            let alterable = estimate_children(&s.alterable_slots);
+            let create_slots = if alterable > 0 { HELPER_CALL } else { 0 };
+            stable + alterable + create_slots + PATCH_FLAG
+        }
+        IR::AlterableSlot(s) => estimate_slot(s),
+        IR::CacheNode(c) => {
+            let expr = match &c.kind {
+                CacheKind::Once => HELPER_CALL,
+                CacheKind::Memo(e) => estimate_js(e) + HELPER_CALL,
+                CacheKind::MemoInVFor { v_for_key, expr } => {
+                    let key = v_for_key.as_ref().map_or(0, estimate_js);
+                    estimate_js(expr) + key + HELPER_CALL
+                }
+            };
+            // `_cache[0] || (_cache[0] = ...)`
+            expr + HELPER_CALL + estimate_ir(&c.child)
+        }
+        IR::CommentCall(c) => c.len() + HELPER_CALL,
+        // `_hoisted_0`
+        IR::Hoisted(_) => ITEM * 2,
+    }
+}
+
+fn estimate_slot(s: &Slot<BaseConvertInfo>) -> usize {
+    let param = s.param.as_ref().map_or(0, estimate_js);
+    // `name: _withCtx(() => [...])`
+    estimate_js(&s.name) + param + HELPER_CALL + estimate_children(&s.body)
+}
+
+fn estimate_js(j: &Js) -> usize {
+    match j {
+        Js::Src(s) | Js::Param(s) => s.len(),
+        Js::Num(_) => 2,
+        Js::StrLit(v) => v.raw.len() + 2,
+        // ops like prefixing add a few bytes
+        Js::Simple(v, _) => v.raw.len() + if v.ops.is_empty() { 0 } else { 5 },
+        Js::FuncSimple { src, .. } => src.raw.len() + ITEM,
+        Js::FuncCompound { body, .. } => body.iter().map(estimate_js).sum::<usize>() + ITEM,
+        Js::Compound(v) => v.iter().map(estimate_js).sum(),
+        Js::Props(ps) => {
+            let props = ps
+                .iter()
+                .map(|(k, v)| estimate_js(k) + estimate_js(v) + ITEM)
+                .sum::<usize>();
+            props + 4
+        }
+        Js::Call(_, args) => HELPER_CALL + args.iter().map(|a| estimate_js(a) + 2).sum::<usize>(),
+        Js::Symbol(_) => HELPER_CALL / 2,
+        Js::Array(v) => v.iter().map(|e| estimate_js(e) + 2).sum::<usize>() + 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::code_writer::CodeWriter;
+    use super::*;
+    use crate::converter::test::base_convert;
+    use crate::SFCInfo;
+
+    fn actual_size(s: &str) -> usize {
+        let mut ir = base_convert(s);
+        ir.top_scope.helpers.ignore_missing();
+        let info = SFCInfo::default();
+        let mut writer = CodeWriter::new(String::new(), Default::default(), &info);
+        writer.generate_root(ir).unwrap();
+        writer.writer.len()
+    }
+
+    #[test]
+    fn test_estimate_within_factor() {
+        let cases = [
+            "hello {{world}}",
+            "<p class='a' :id='b'>{{c}}</p>",
+            "<div><p v-if='a'/><span v-else/></div>",
+            "<ul><li v-for='item in items' :key='item.id'>{{item.name}}</li></ul>",
+            "<comp><template #header='{ a }'>{{a}}</template></comp>",
+            "<p v-once>{{a}}</p><!--comment-->",
+        ];
+        for case in cases.iter() {
+            let estimated = estimate_size(&base_convert(case));
+            let actual = actual_size(case);
+            assert!(
+                estimated * 3 >= actual && estimated <= actual * 3,
+                "{}: estimated {} but actual {}",
+                case,
+                estimated,
+                actual
+            );
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use super::{
     SFCInfo,
-    codegen::{CodeGenerateOption, CodeGenerator, CodeGen, ScriptMode, CodeGenInfo},
+    codegen::{
+        CodeGenerateOption, CodeGenerator, CodeGen, ScriptMode, CodeGenInfo, ExportStyle,
+        CodegenTarget, estimate_size,
+    },
     converter::{
-        no_op_directive_convert, BaseConvertInfo as BaseInfo, BaseConverter, BaseRoot,
+        no_op_directive_convert, BaseConvertInfo as BaseInfo, BaseConverter, BaseRoot, CompatMode,
         ConvertOption, Converter, DirConvertFn, V_BIND, V_MODEL,
     },
-    error::{NoopErrorHandler, RcErrHandle},
+    error::{CompilationError, NoopErrorHandler, RcErrHandle, VecErrorHandler},
     flags::RuntimeHelper,
     parser::{Element, ParseOption, Parser, WhitespaceStrategy, AstRoot},
     scanner::{ScanOption, Scanner, TextMode, Tokens},
@@ -16,14 +19,21 @@ use super::{
         collect_entities::EntityCollector,
         mark_patch_flag::PatchFlagMarker,
         mark_slot_flag::SlotFlagMarker,
+        normalize_props::{HoistForClass, NormalizeProp},
         optimize_text::TextOptimizer,
         pass::{Scope, SharedInfoPasses},
         process_expression::ExpressionProcessor,
     },
 };
 
-use rustc_hash::FxHashMap;
-use std::{io, rc::Rc, marker::PhantomData};
+use rustc_hash::{FxHashMap, FxHasher};
+use std::{
+    collections::hash_map::Entry,
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
+    rc::Rc,
+};
 
 pub struct CompileOption {
     /// e.g. platform native elements, e.g. `<div>` for browsers
@@ -58,6 +68,13 @@ pub struct CompileOption {
 
     /// platform speicific helper
     pub helper_strs: &'static [&'static str],
+    /// Where to import a custom helper from in module mode, e.g. a plugin
+    /// registers `RH(RH::INTERNAL_MAX + n)` with its name in helper_strs
+    /// and returns `Some("my-runtime")` here. None imports from runtime module.
+    pub helper_source: fn(RuntimeHelper) -> Option<&'static str>,
+    /// Rename helpers for forks or re-exports of the runtime, e.g. returns
+    /// `Some("myCreateVNode")` for CREATE_VNODE. None uses the default name.
+    pub helper_name_override: fn(RuntimeHelper) -> Option<&'static str>,
 
     /// Whether to keep comments in the templates AST.
     /// This defaults to `true` in development and `false` in production builds.
@@ -68,7 +85,9 @@ pub struct CompileOption {
     /// An object of { name: transform } to be applied to every directive attribute
     /// node found on element nodes.
     pub directive_converters: FxHashMap<&'static str, DirConvertFn>,
-    /// Hoist static VNodes and props objects to `_hoisted_x` constants
+    /// Hoist static element subtrees to `_hoisted_x` constants.
+    /// Turn it off to read inline vnode creation when debugging output.
+    /// NB: hoisting is done by the `HoistStatic` pass in DOM passes.
     /// @default false
     pub hoist_static: bool,
     /// Cache v-on handlers to avoid creating new inline functions on each render,
@@ -91,6 +110,69 @@ pub struct CompileOption {
     /// used with `new Function(code)()` to generate a render function at runtime.
     /// @default 'function'
     pub mode: ScriptMode,
+    /// How `module` mode exports the render function.
+    /// e.g. `export default function render` for ExportStyle::Default.
+    /// @default ExportStyle::None
+    pub export_style: ExportStyle,
+    /// Only generate the `return ...` body of render function.
+    /// Useful for embedding output into another function, e.g. script setup.
+    /// @default false
+    pub body_only: bool,
+    /// Drop unreferenced `_ctx`/`_cache` params from render signature,
+    /// e.g. `function render(_ctx)` for templates without cached nodes.
+    /// Useful for no-unused-vars lint in prefixIdentifiers or module mode.
+    /// @default false
+    pub elide_unused_params: bool,
+    /// Append `render.__file = "Foo.vue"` using SFCInfo's self_name so devtools
+    /// can identify the component. Only in dev build and module mode since
+    /// function mode returns the render function as an expression.
+    /// @default false
+    pub emit_file_name: bool,
+    /// Fail compilation if a helper is used but not imported, which
+    /// usually means a custom pass forgets to collect it.
+    /// @default false
+    pub check_helpers: bool,
+    /// Minimal one-line output without newline and indentation,
+    /// statements are separated by semicolons.
+    /// @default false
+    pub compact: bool,
+    /// Prefix vnode calls with `/* line N */` comments pointing to their
+    /// template lines. A lightweight alternative to source map, dev only.
+    /// @default false
+    pub line_comments: bool,
+    /// Annotate block children with indices of the dynamic ones, e.g.
+    /// `/* dynamic children: 0, 2 */`, which runtime tracks in dynamicChildren.
+    /// For debugging block optimization, dev only.
+    /// @default false
+    pub dynamic_children_comments: bool,
+    /// Kind of render function to generate, e.g. `function render() {}` for
+    /// DOM or an inline arrow `(_ctx, _cache) => {}` for embedders returning it
+    /// from setup(). The arrow is not named so module mode always exports it by
+    /// default, inline SFC render is always the arrow.
+    /// @default CodegenTarget::Dom
+    pub target: CodegenTarget,
+    /// Annotate the render function declaration with `/* @__NO_SIDE_EFFECTS__ */`
+    /// so bundlers can drop unused render calls. Only module mode emits it,
+    /// inline arrow is not annotated.
+    /// @default false
+    pub no_side_effects: bool,
+    /// Mark hoists, asset resolution and render return with `// <section>`
+    /// comments for reviewing generated code. Dev only, ignored in compact.
+    /// @default false
+    pub section_comments: bool,
+    /// Wrap output in an IIFE for `<script>` tag consumption, e.g.
+    /// `(function () { ...return function render() {} })()`.
+    /// Only function mode output can be wrapped, module mode is untouched.
+    /// @default false
+    pub iife: bool,
+    /// Assign the IIFE result to a global, e.g. `window.render`. Implies `iife`.
+    /// @default None
+    pub global_var: Option<String>,
+    /// Put arguments of a generated call or items of an array on their own
+    /// indented lines if the list would exceed this width. Ignored in compact.
+    /// 0 always keeps lists in one line.
+    /// @default 80
+    pub max_line_length: usize,
     /// Generate source map?
     /// @default false
     pub source_map: bool,
@@ -98,6 +180,19 @@ pub struct CompileOption {
     /// e.g. SSR can set it to false since SSR is executed only once per request.
     /// @default true
     pub need_reactivity: bool,
+    /// Vue 2 compatibility for migration build, e.g. `:prop.sync`.
+    /// @default CompatMode::None
+    pub compat: CompatMode,
+    /// Emit optional chaining for member access in interpolation, e.g. `a?.b`.
+    /// Useful when bindings may be undefined.
+    /// @default false
+    pub optional_chaining: bool,
+    /// Keep every prop in template order. A duplicated class, style or handler
+    /// starts a new object merged by mergeProps at runtime instead of being
+    /// merged into its first occurrence, e.g. `<p class="a" id="b" :class="c">`
+    /// becomes `_mergeProps({ class: "a", id: "b" }, { class: c })`.
+    /// @default false
+    pub preserve_prop_order: bool,
     /// Custom error reporter. Default is noop.
     pub error_handler: RcErrHandle,
     // deleted options
@@ -138,6 +233,8 @@ impl Default for CompileOption {
             delimiters: ("{{".into(), "}}".into()),
             whitespace: WhitespaceStrategy::Preserve,
             helper_strs: &[],
+            helper_source: |_| None,
+            helper_name_override: |_| None,
             preserve_comments: None,
             is_dev: true,
             directive_converters,
@@ -147,8 +244,25 @@ impl Default for CompileOption {
                 prefix_identifier: false,
                 runtime_global_name: "Vue".into(),
             },
+            export_style: ExportStyle::None,
+            body_only: false,
+            elide_unused_params: false,
+            emit_file_name: false,
+            check_helpers: false,
+            compact: false,
+            line_comments: false,
+            dynamic_children_comments: false,
+            target: CodegenTarget::Dom,
+            no_side_effects: false,
+            section_comments: false,
+            iife: false,
+            global_var: None,
+            max_line_length: 80,
             source_map: false,
             need_reactivity: true,
+            compat: CompatMode::None,
+            optional_chaining: false,
+            preserve_prop_order: false,
             error_handler: Rc::new(NoopErrorHandler),
         }
     }
@@ -180,6 +294,9 @@ impl CompileOption {
             is_dev: self.is_dev,
             directive_converters: self.directive_converters.clone(),
             need_reactivity: self.need_reactivity,
+            compat: self.compat,
+            optional_chaining: self.optional_chaining,
+            preserve_prop_order: self.preserve_prop_order,
         }
     }
     pub fn transforming(&self) -> TransformOption {
@@ -187,7 +304,7 @@ impl CompileOption {
             ScriptMode::Function {
                 prefix_identifier, ..
             } => prefix_identifier,
-            ScriptMode::Module { .. } => true,
+            ScriptMode::Module { .. } | ScriptMode::Cjs { .. } => true,
         };
         TransformOption {
             prefix_identifier: prefix,
@@ -198,8 +315,25 @@ impl CompileOption {
         CodeGenerateOption {
             is_dev: self.is_dev,
             mode: self.mode.clone(),
+            export_style: self.export_style.clone(),
+            body_only: self.body_only,
+            elide_unused_params: self.elide_unused_params,
+            emit_file_name: self.emit_file_name,
             source_map: self.source_map,
             helper_strs: self.helper_strs,
+            helper_source: self.helper_source,
+            helper_name_override: self.helper_name_override,
+            is_void_tag: self.is_void_tag,
+            check_helpers: self.check_helpers,
+            compact: self.compact,
+            line_comments: self.line_comments,
+            dynamic_children_comments: self.dynamic_children_comments,
+            target: self.target,
+            no_side_effects: self.no_side_effects,
+            section_comments: self.section_comments,
+            iife: self.iife,
+            global_var: self.global_var.clone(),
+            max_line_length: self.max_line_length,
         }
     }
 }
@@ -232,6 +366,8 @@ where
     P: CorePass<BaseInfo<'a>>,
 {
     writer: fn() -> W,
+    /// grows the writer by the estimated output size before codegen
+    reserve: Option<fn(&mut W, usize)>,
     passes: fn(&'a SFCInfo<'a>, &CompileOption) -> P,
     option: CompileOption,
     scanner: Scanner,
@@ -251,6 +387,7 @@ where
     ) -> Self {
         Self {
             writer,
+            reserve: None,
             passes,
             scanner: Scanner::new(option.scanning()),
             parser: Parser::new(option.parsing()),
@@ -258,6 +395,12 @@ where
             pd: PhantomData,
         }
     }
+    /// Reserve the writer's capacity by `estimate_size` of the IR,
+    /// e.g. `Vec::reserve` to avoid reallocation when writing output.
+    pub fn with_reserve(mut self, reserve: fn(&mut W, usize)) -> Self {
+        self.reserve = Some(reserve);
+        self
+    }
     fn get_converter(&self) -> BaseConverter {
         let eh = self.get_error_handler();
         let option = self.option.converting();
@@ -290,13 +433,19 @@ where
     }
     fn generate(&self, ir: Self::IR, sfc_info: Self::Info) -> Self::Output {
         let mut writer = (self.writer)();
+        if let Some(reserve) = self.reserve {
+            reserve(&mut writer, estimate_size(&ir));
+        }
         let option = self.option.codegen();
         let generator = CodeGen::new(option);
         let gen_info = CodeGenInfo {
             writer: &mut writer,
             sfc_info,
         };
-        generator.generate(ir, gen_info)?;
+        let result = generator.generate(ir, gen_info)?;
+        if let Some(err) = result.errors.into_iter().next() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
         Ok(writer)
     }
     fn get_error_handler(&self) -> RcErrHandle {
@@ -304,6 +453,185 @@ where
     }
 }
 
+/// Scan, parse and convert template to IR without transform or codegen.
+/// Useful for tooling like editors or linters that analyze templates.
+/// Errors are returned instead of reported to `option.error_handler`.
+/// ```
+/// use vue_compiler_core::compiler::{parse_to_ir, CompileOption};
+/// let option = CompileOption::default();
+/// let sfc_info = Default::default();
+/// let root = parse_to_ir("<p>{{ msg }}</p><br/>", &option, &sfc_info).unwrap();
+/// assert_eq!(root.body.len(), 2);
+/// ```
+pub fn parse_to_ir<'a>(
+    source: &'a str,
+    option: &CompileOption,
+    sfc_info: &'a SFCInfo<'a>,
+) -> Result<BaseRoot<'a>, Vec<CompilationError>> {
+    let errors = Rc::new(VecErrorHandler::new());
+    let eh: RcErrHandle = errors.clone();
+    let tokens = Scanner::new(option.scanning()).scan(source, eh.clone());
+    let ast = Parser::new(option.parsing()).parse(tokens, eh.clone());
+    let converter = BaseConverter::new(eh, option.converting());
+    let ir = converter.convert_ir(ast, sfc_info);
+    let errors = std::mem::take(&mut *errors.error_mut());
+    if errors.is_empty() {
+        Ok(ir)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Opt-in memoization of compiled output, e.g. for hot reload or SSR
+/// compiling the same template repeatedly. Entries are keyed by the hash
+/// of source, options, passes and SFCInfo.
+/// ```
+/// use vue_compiler_core::compiler::{BaseCompiler, CompileCache, get_base_passes};
+/// let sfc_info = Default::default();
+/// let compiler = BaseCompiler::new(Vec::new, get_base_passes, Default::default());
+/// let mut cache = CompileCache::default();
+/// let out = cache.compile(&compiler, "<p/>", &sfc_info).unwrap().to_vec();
+/// assert_eq!(cache.compile(&compiler, "<p/>", &sfc_info).unwrap(), &out[..]);
+/// ```
+#[derive(Default)]
+pub struct CompileCache {
+    outputs: FxHashMap<u64, Vec<u8>>,
+}
+
+impl CompileCache {
+    /// Returns cached output or runs the compiler on cache miss.
+    /// Failed compilation is not cached.
+    pub fn compile<'a, P>(
+        &mut self,
+        compiler: &BaseCompiler<'a, P, Vec<u8>>,
+        source: &'a str,
+        sfc_info: &'a SFCInfo<'a>,
+    ) -> io::Result<&[u8]>
+    where
+        P: CorePass<BaseInfo<'a>>,
+    {
+        let mut h = FxHasher::default();
+        hash_compile_input(source, &compiler.option, sfc_info).hash(&mut h);
+        (compiler.passes as usize).hash(&mut h);
+        let output = match self.outputs.entry(h.finish()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(compiler.compile(source, sfc_info)?),
+        };
+        Ok(output)
+    }
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.outputs.clear()
+    }
+}
+
+/// Hash of source, SFCInfo and every option affecting output.
+/// Function options are compared by address.
+pub fn hash_compile_input(source: &str, option: &CompileOption, sfc_info: &SFCInfo) -> u64 {
+    // destructure so new options must be considered here
+    let CompileOption {
+        is_native_tag,
+        is_void_tag,
+        is_pre_tag,
+        get_builtin_component,
+        is_custom_element,
+        get_namespace,
+        get_text_mode,
+        delimiters,
+        whitespace,
+        helper_strs,
+        helper_source,
+        helper_name_override,
+        preserve_comments,
+        is_dev,
+        directive_converters,
+        hoist_static,
+        cache_handlers,
+        mode,
+        export_style,
+        body_only,
+        elide_unused_params,
+        emit_file_name,
+        check_helpers,
+        compact,
+        line_comments,
+        dynamic_children_comments,
+        target,
+        no_side_effects,
+        section_comments,
+        iife,
+        global_var,
+        max_line_length,
+        source_map,
+        need_reactivity,
+        compat,
+        optional_chaining,
+        preserve_prop_order,
+        error_handler: _,
+    } = option;
+    let mut h = FxHasher::default();
+    source.hash(&mut h);
+    let fns = [
+        *is_native_tag as usize,
+        *is_void_tag as usize,
+        *is_pre_tag as usize,
+        *get_builtin_component as usize,
+        *is_custom_element as usize,
+        *get_namespace as usize,
+        *get_text_mode as usize,
+        *helper_source as usize,
+        *helper_name_override as usize,
+    ];
+    fns.hash(&mut h);
+    let mut convs: Vec<_> = directive_converters
+        .iter()
+        .map(|(name, f)| (*name, *f as usize))
+        .collect();
+    convs.sort_unstable();
+    convs.hash(&mut h);
+    (delimiters, whitespace, helper_strs, preserve_comments).hash(&mut h);
+    (mode, export_style, compat, global_var, target).hash(&mut h);
+    max_line_length.hash(&mut h);
+    [
+        *is_dev,
+        *hoist_static,
+        *cache_handlers,
+        *body_only,
+        *elide_unused_params,
+        *emit_file_name,
+        *check_helpers,
+        *compact,
+        *line_comments,
+        *dynamic_children_comments,
+        *no_side_effects,
+        *section_comments,
+        *iife,
+        *source_map,
+        *need_reactivity,
+        *optional_chaining,
+        *preserve_prop_order,
+    ]
+    .hash(&mut h);
+    let SFCInfo {
+        inline,
+        slotted,
+        scope_id,
+        binding_metadata,
+        self_name,
+    } = sfc_info;
+    (inline, slotted, scope_id, self_name).hash(&mut h);
+    let mut bindings: Vec<_> = binding_metadata.iter().collect();
+    bindings.sort_unstable_by_key(|(name, _)| **name);
+    bindings.hash(&mut h);
+    binding_metadata.is_setup().hash(&mut h);
+    h.finish()
+}
+
 pub fn get_base_passes<'a>(
     sfc_info: &'a SFCInfo<'a>,
     opt: &CompileOption,
@@ -322,6 +650,11 @@ pub fn get_base_passes<'a>(
         TextOptimizer,
         EntityCollector::default(),
         PatchFlagMarker,
+        // same props normalization as dom passes, not only merged class:
+        // normalizeClass/normalizeStyle values, normalizeProps for dynamic
+        // keys and guardReactiveProps for a lone v-bind object
+        NormalizeProp,
+        HoistForClass::default(),
         SharedInfoPasses {
             passes: shared,
             shared_info: Scope::default(),
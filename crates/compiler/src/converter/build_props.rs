@@ -1,4 +1,4 @@
-use super::{BaseConversion as BC, CoreConversion, Element, VStr};
+use super::{BaseConversion as BC, CompatMode, CoreConversion, Element, VStr};
 use crate::{
     flags::{self, PatchFlag, RuntimeHelper},
     ir::{JsExpr as Js, Prop},
@@ -36,6 +36,8 @@ struct PropArgs<'a> {
     pending_props: Props<'a>,
     /// merged prop argument, e.g. obj
     merge_args: Args<'a>,
+    /// split pending props at duplicated class/style instead of deduping
+    preserve_order: bool,
 }
 
 #[derive(Default)]
@@ -47,9 +49,10 @@ struct CollectProps<'a> {
 }
 
 impl<'a> CollectProps<'a> {
-    fn new(e: &Element<'a>) -> Self {
+    fn new(bc: &BC, e: &Element<'a>) -> Self {
         let mut s = Self::default();
         s.prop_flags.is_component = e.is_component();
+        s.prop_args.preserve_order = bc.option.preserve_prop_order;
         s
     }
 }
@@ -59,11 +62,16 @@ type Args<'a> = Vec<Js<'a>>;
 type Dir<'a> = (Directive<'a>, Option<RuntimeHelper>);
 type Dirs<'a> = Vec<Dir<'a>>;
 
+/// Props are emitted in template order, no key is moved before others.
+/// Duplicated class/style are merged into their first occurrence, or split
+/// into another mergeProps argument with `preserve_prop_order`, and
+/// `v-bind="obj"` splits props into ordered mergeProps arguments.
+/// Injected v-if/v-for key is appended last.
 pub fn build_props<'a, T>(bc: &BC<'a>, e: &mut Element<'a>, elm_props: T) -> BuildProps<'a>
 where
     T: IntoIterator<Item = ElemProp<'a>>,
 {
-    let mut cp = CollectProps::new(e);
+    let mut cp = CollectProps::new(bc, e);
     elm_props.into_iter().for_each(|prop| match prop {
         ElemProp::Dir(dir) => collect_dir(bc, e, dir, &mut cp),
         ElemProp::Attr(attr) => collect_attr(bc, e, attr, &mut cp),
@@ -75,6 +83,12 @@ where
         ..
     } = cp;
     let patch_flag = build_patch_flag(cp.prop_flags, &runtime_dirs, &dynamic_props);
+    // full props diff covers every key, the name list is redundant
+    let dynamic_props = if patch_flag.contains(PatchFlag::FULL_PROPS) {
+        FxHashSet::default()
+    } else {
+        dynamic_props
+    };
     BuildProps {
         props: prop_expr,
         directives: runtime_dirs,
@@ -99,6 +113,8 @@ fn collect_attr<'a>(bc: &BC, e: &Element<'a>, attr: Attribute<'a>, cp: &mut Coll
         if bc.sfc_info.inline && !val.is_empty() {
             value_expr = process_inline_ref(val);
         }
+    } else if name == "style" {
+        value_expr = parse_static_style(val);
     }
     cp.prop_args
         .pending_props
@@ -135,12 +151,16 @@ fn collect_dir<'a>(
         DirConv::Preserve => return cp.runtime_dirs.push((dir, None)),
         DirConv::Dropped => return,
     };
+    let compat_sync = bc.option.compat == CompatMode::V2 && is_v_bind_sync(&dir);
     match runtime {
         Ok(helper) => cp.runtime_dirs.push((dir, Some(helper))),
         Err(true) => cp.runtime_dirs.push((dir, None)),
         Err(false) => (),
     }
-    if let Js::Props(props) = value {
+    if let Js::Props(mut props) = value {
+        if compat_sync {
+            add_sync_handler(&mut props);
+        }
         props.iter().for_each(|p| analyze_patch_flag(p, cp));
         cp.prop_args.pending_props.extend(props);
         return;
@@ -151,14 +171,49 @@ fn collect_dir<'a>(
     cp.prop_args.merge_args.push(value);
 }
 
+fn is_v_bind_sync(dir: &Directive) -> bool {
+    dir.name == "bind" && dir.modifiers.contains(&"sync")
+}
+
+/// v2 `:prop.sync="val"` is sugar for `:prop="val" @update:prop="val = $event"`
+fn add_sync_handler(props: &mut Props) {
+    let (mut name, mut val) = match props.first() {
+        Some((Js::StrLit(name), Js::Simple(val, _))) => (*name, *val),
+        _ => return,
+    };
+    let event_name = Js::StrLit(*name.be_vmodel());
+    let assignment = Js::func(*val.assign_event());
+    props.push((event_name, assignment));
+}
+
 fn flush_pending_props(prop_args: &mut PropArgs) {
     // flush existing props to an object
     if prop_args.pending_props.is_empty() {
         return;
     }
     let arg = mem::take(&mut prop_args.pending_props);
-    let arg = dedupe_properties(arg);
-    prop_args.merge_args.push(Js::Props(arg));
+    if prop_args.preserve_order {
+        let args = split_properties(arg).into_iter().map(Js::Props);
+        prop_args.merge_args.extend(args);
+    } else {
+        let arg = dedupe_properties(arg);
+        prop_args.merge_args.push(Js::Props(arg));
+    }
+}
+
+/// static style is parsed to object so it can merge with :style object
+fn parse_static_style(val: VStr) -> Js {
+    let props = util::parse_string_style(val.raw)
+        .into_iter()
+        .map(|(k, v)| {
+            let v = VStr {
+                raw: v,
+                ops: val.ops,
+            };
+            (Js::str_lit(k), Js::StrLit(v))
+        })
+        .collect();
+    Js::Props(props)
 }
 
 fn process_inline_ref(_val: VStr) -> Js {
@@ -189,13 +244,33 @@ fn dedupe_properties(props: Props) -> Props {
     ret
 }
 
+/// Like dedupe_properties but a duplicated class/style/handler starts a new object,
+/// which mergeProps merges at runtime, so every key keeps its template order.
+fn split_properties(props: Props) -> Vec<Props> {
+    let mut known_props = FxHashSet::default();
+    let mut ret = vec![vec![]];
+    for (key, val) in props {
+        if let Js::StrLit(name) = &key {
+            if !known_props.insert(*name) {
+                if !util::is_mergeable_prop(name) {
+                    // TODO: should remove by parser
+                    continue;
+                }
+                ret.push(vec![]);
+            }
+        }
+        ret.last_mut().unwrap().push((key, val));
+    }
+    ret
+}
+
 fn merge_as_array<'a>(existing: &mut Prop<'a>, incoming: Js<'a>) {
     let val = &mut existing.1;
     if let Js::Array(arr) = val {
         arr.push(incoming);
     } else {
         let v = mem::replace(val, Js::Src(""));
-        let mut arr = Js::Array(vec![v]);
+        let mut arr = Js::Array(vec![v, incoming]);
         mem::swap(val, &mut arr);
     }
 }
@@ -43,6 +43,7 @@ pub fn convert_element<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
         is_block,
         disable_tracking: false,
         is_component: e.is_component(),
+        location: e.location,
     };
     IRNode::VNodeCall(vnode)
 }
@@ -52,7 +53,7 @@ pub fn convert_template<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
     debug_assert!(e.tag_type == ElementType::Template);
     check_wrong_slot(bc, &e, ErrorKind::VSlotTemplateMisplaced);
     // TODO: optimize away template if it has one stable element child
-    // TODO: pass key property to the direct element child
+    // NB: key stays on the fragment, e.g. keyed <template v-for>
     // template here is purely a fragment that groups element.
     let mut patch_flag = PatchFlag::STABLE_FRAGMENT;
     let child_count = e
@@ -72,6 +73,7 @@ pub fn convert_template<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
     }
     IRNode::VNodeCall(VNodeIR {
         tag: Js::Symbol(RuntimeHelper::FRAGMENT),
+        location: e.location,
         children: bc.convert_children(e.children),
         patch_flag,
         props,
@@ -373,7 +375,8 @@ fn get_variety_from_binding<'a: 'b, 'b>(
 
 #[cfg(test)]
 mod test {
-    use super::super::test::base_convert;
+    use super::super::test::{base_convert, option_convert};
+    use super::super::{CompatMode, ConvertOption};
     use super::*;
     use crate::cast;
     #[test]
@@ -385,4 +388,26 @@ mod test {
         assert_eq!(tag.into_string(), "_component_comp");
         assert!(vn.is_component);
     }
+
+    fn get_prop_keys(mut body: Vec<BaseIR>) -> Vec<String> {
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        props
+            .into_iter()
+            .map(|(k, _)| cast!(k, Js::StrLit).into_string())
+            .collect()
+    }
+    #[test]
+    fn test_compat_v_bind_sync() {
+        let src = "<comp :foo.sync='bar'/>";
+        let mut option = ConvertOption {
+            directive_converters: vec![super::super::v_bind::V_BIND].into_iter().collect(),
+            ..Default::default()
+        };
+        let keys = get_prop_keys(option_convert(src, option.clone()).body);
+        assert_eq!(keys, vec!["foo"]);
+        option.compat = CompatMode::V2;
+        let keys = get_prop_keys(option_convert(src, option).body);
+        assert_eq!(keys, vec!["foo", "onUpdate:foo"]);
+    }
 }
@@ -36,8 +36,8 @@ mod v_slot;
 use crate::{
     flags::{HelperCollector, RuntimeHelper},
     ir::{ConvertInfo, IRNode, IRRoot, JsExpr, TextIR},
-    parser::{SourceNode, TextNode},
-    util::{get_core_component, VStr},
+    parser::{ElementType, SourceNode, TextNode},
+    util::{find_dir_empty, get_core_component, VStr},
     SFCInfo,
 };
 pub use v_bind::V_BIND;
@@ -67,11 +67,33 @@ pub trait Converter: Sized {
     fn convert_ir<'a>(&self, ast: AstRoot<'a>, info: Self::Info<'a>) -> Self::IR<'a>;
 }
 
+/// A bare root `<template>` wrapping one child is only a container,
+/// e.g. copied from SFC, so its child becomes the root instead of
+/// a template element. Templates with v-if/v-for/v-slot are fragments.
+fn unwrap_root_template(mut children: Vec<AstNode>) -> Vec<AstNode> {
+    let is_wrapper = match &children[..] {
+        [AstNode::Element(e)] => {
+            e.tag_name == "template"
+                && e.tag_type == ElementType::Plain
+                && e.properties.is_empty()
+                && e.children.len() == 1
+        }
+        _ => false,
+    };
+    if !is_wrapper {
+        return children;
+    }
+    match children.pop() {
+        Some(AstNode::Element(e)) => e.children,
+        _ => unreachable!(),
+    }
+}
+
 /// Default implementation  sketch can be used in DOM/SSR.
 /// Other platform might invent and use their own IR.
 pub trait CoreConversion<'a, T: ConvertInfo> {
     fn convert_core_ir(&self, ast: AstRoot<'a>) -> IRRoot<T> {
-        let body = self.convert_children(ast.children);
+        let body = self.convert_children(unwrap_root_template(ast.children));
         IRRoot {
             body,
             top_scope: T::TopType::default(),
@@ -106,7 +128,7 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         // in non reactive build, we can skip cache related dir
         if !self.is_reactive_build() {
             let vfor = pre_convert_for(self, &mut e);
-            let mut n = self.dispatch_element(e);
+            let mut n = self.dispatch_for_item(e, vfor.is_some());
             if let Some(d) = vfor {
                 n = self.convert_for(d, n);
             }
@@ -116,7 +138,7 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         let once = pre_convert_once(&mut e);
         let vfor = pre_convert_for(self, &mut e);
         let memo = pre_convert_memo(&mut e);
-        let mut n = self.dispatch_element(e);
+        let mut n = self.dispatch_for_item(e, vfor.is_some());
         if let Some(d) = memo {
             n = self.convert_memo(d, n);
         }
@@ -129,6 +151,14 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         // reverse order
         n
     }
+    /// v-if left on a v-for element becomes the conditional of each item
+    fn dispatch_for_item(&self, e: Element<'a>, in_for: bool) -> IRNode<T> {
+        if in_for && find_dir_empty(&e, "if").is_some() {
+            self.convert_if(vec![e], 0)
+        } else {
+            self.dispatch_element(e)
+        }
+    }
     fn dispatch_element(&self, e: Element<'a>) -> IRNode<T> {
         use super::parser::ElementType::{SlotOutlet, Template};
         match e.tag_type {
@@ -208,6 +238,9 @@ pub struct TopScope<'a> {
     pub directives: FxHashSet<VStr<'a>>,
     /// hoisted vnode/text/js object
     pub hoists: Vec<BaseIR<'a>>,
+    /// loop invariant v-for classes normalized before render returns,
+    /// declared as `_class`, `_class1`... in the render body
+    pub classes: Vec<JsExpr<'a>>,
     /// assets need to be imported for template, e.g. image
     pub imports: Vec<ImportItem<'a>>,
     /// counters for temporary variables created in template
@@ -232,6 +265,16 @@ pub type DirConvertFn =
     for<'a> fn(&mut Directive<'a>, &Element<'a>, &dyn ErrorHandler) -> CoreDirConvRet<'a>;
 pub type DirectiveConverter = (&'static str, DirConvertFn);
 
+/// Vue 2 compatibility for the migration build.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompatMode {
+    /// Vue 3 semantics only.
+    None,
+    /// Emit v2 equivalents where v2 and v3 codegen diverge.
+    /// e.g. `:prop.sync` also emits `onUpdate:prop` handler.
+    V2,
+}
+
 #[derive(Clone)]
 pub struct ConvertOption {
     /// For platform developers. Registers platform specific components written in JS.
@@ -240,6 +283,11 @@ pub struct ConvertOption {
     pub directive_converters: FxHashMap<&'static str, DirConvertFn>,
     pub is_dev: bool,
     pub need_reactivity: bool,
+    pub compat: CompatMode,
+    /// Emit `a?.b` for member access in interpolation.
+    pub optional_chaining: bool,
+    /// Split props at duplicated class/style/handler to keep template order.
+    pub preserve_prop_order: bool,
 }
 
 impl Default for ConvertOption {
@@ -248,6 +296,9 @@ impl Default for ConvertOption {
             get_builtin_component: get_core_component,
             is_dev: true,
             need_reactivity: true,
+            compat: CompatMode::None,
+            optional_chaining: false,
+            preserve_prop_order: false,
             directive_converters: FxHashMap::default(),
         }
     }
@@ -347,7 +398,11 @@ impl<'a> CoreConversion<'a, BaseConvertInfo<'a>> for BaseConversion<'a> {
         })
     }
     fn convert_interpolation(&self, interp: SourceNode<'a>) -> BaseIR<'a> {
-        let expr = JsExpr::simple(interp.source);
+        let mut src = VStr::raw(interp.source);
+        if self.option.optional_chaining && v_on::is_member_expression(VStr::raw(src.trim())) {
+            src.optional_chain();
+        }
+        let expr = JsExpr::simple(src);
         let call = JsExpr::Call(RuntimeHelper::TO_DISPLAY_STRING, vec![expr]);
         IRNode::TextCall(TextIR {
             fast_path: false,
@@ -398,6 +453,14 @@ pub mod test {
         let ast = base_parse(s);
         bc.convert_ir(ast, &SFC_INFO)
     }
+    pub fn option_convert(s: &str, option: ConvertOption) -> BaseRoot {
+        let bc = BC {
+            err_handle: Rc::new(TestErrorHandler),
+            option: Rc::new(option),
+        };
+        let ast = base_parse(s);
+        bc.convert_ir(ast, &SFC_INFO)
+    }
     pub fn handler_convert(s: &str) -> BaseRoot {
         let convs = vec![
             v_bind::V_BIND,
@@ -6,6 +6,7 @@ use super::{
     JsExpr as Js,
 };
 use crate::parser::DirectiveArg;
+use crate::util::{is_simple_identifier, VStr};
 
 // this module process v-bind without arg and with arg.
 pub fn convert_v_bind<'a>(
@@ -13,7 +14,9 @@ pub fn convert_v_bind<'a>(
     _: &Element<'a>,
     eh: &dyn ErrorHandler,
 ) -> CoreDirConvRet<'a> {
-    let expr = if let Some(error) = dir.check_empty_expr(ErrorKind::VBindNoExpression) {
+    let expr = if let Some(expr) = same_name_shorthand(dir) {
+        expr
+    } else if let Some(error) = dir.check_empty_expr(ErrorKind::VBindNoExpression) {
         eh.on_error(error);
         if dir.argument.is_none() {
             return DirectiveConvertResult::Dropped;
@@ -61,6 +64,19 @@ pub fn convert_v_bind<'a>(
     }
 }
 
+/// Vue 3.4 shorthand: <p :id> is the same as <p :id="id">
+fn same_name_shorthand<'a>(dir: &Directive<'a>) -> Option<Js<'a>> {
+    if !dir.has_empty_expr() {
+        return None;
+    }
+    match dir.argument {
+        Some(DirectiveArg::Static(name)) if is_simple_identifier(VStr::raw(name)) => {
+            Some(Js::simple(name))
+        }
+        _ => None,
+    }
+}
+
 pub const V_BIND: DirectiveConverter = ("bind", convert_v_bind);
 
 #[cfg(test)]
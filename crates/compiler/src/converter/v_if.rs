@@ -42,11 +42,12 @@ impl<'a> PreGroupIter<'a> {
     }
 
     fn next_standalone(&mut self) -> Option<PreGroup<'a>> {
-        // either iter is empty or has no if/else/else-if
+        // either iter is empty or has no if/else/else-if, or has v-for with v-if
         debug_assert!(self
             .inner
             .peek()
             .and_then(|n| n.get_element())
+            .filter(|e| !is_v_for_with_v_if(e))
             .and_then(|e| find_dir_empty(e, ["if", "else", "else-if"]))
             .is_none());
         self.inner.next().map(PreGroup::StandAlone)
@@ -61,6 +62,10 @@ impl<'a> Iterator for PreGroupIter<'a> {
                 .get_element()
                 .and_then(|e| find_dir_empty(e, ["if", "else-if", "else"]));
             if let Some(d) = found {
+                // v-if is checked per item inside v-for on the same element
+                if n.get_element().map_or(false, is_v_for_with_v_if) {
+                    return self.flush_group().or_else(|| self.next_standalone());
+                }
                 // separate v-if into different groups
                 if d.get_ref().name == "if" && !self.group.is_empty() {
                     return self.flush_group();
@@ -95,6 +100,11 @@ impl<'a> Iterator for PreGroupIter<'a> {
     }
 }
 
+/// `<p v-for="i in list" v-if="i.ok">` renders `cond ? vnode : comment` per item.
+fn is_v_for_with_v_if(e: &Element) -> bool {
+    find_dir_empty(e, "if").is_some() && find_dir_empty(e, "for").is_some()
+}
+
 /// pre group adjacent elements with v-if
 // using generator here will be super cool
 pub fn pre_group_v_if(children: Vec<AstNode>) -> impl Iterator<Item = PreGroup> {
@@ -29,8 +29,11 @@ pub fn check_build_as_slot(e: &Element, tag: &Js) -> bool {
     debug_assert!(e.tag_type != ElementType::Template);
     use RuntimeHelper as RH;
     match tag {
-        Js::Symbol(RH::KEEP_ALIVE) => true,
-        Js::Symbol(RH::TELEPORT) => true,
+        // KeepAlive is compiled with raw children instead of slot functions
+        // so that it can be used inside Transition or other Transition-wrapping HOCs.
+        Js::Symbol(RH::KEEP_ALIVE) => false,
+        // Teleport is not a real component and has dedicated runtime handling
+        Js::Symbol(RH::TELEPORT) => false,
         _ => e.is_component(),
     }
 }
@@ -247,8 +247,12 @@ impl HelperCollector {
         self.0 == 0 || (cfg!(test) && self.0 == !0)
     }
     pub fn collect(&mut self, helper: RuntimeHelper) {
+        debug_assert!(helper.0 < 64, "helper exceeds collector capacity");
         self.0 |= 1 << helper.0;
     }
+    pub fn remove(&mut self, helper: RuntimeHelper) {
+        self.0 &= !(1 << helper.0);
+    }
     pub fn contains(&self, helper: RuntimeHelper) -> bool {
         (self.0 & (1 << helper.0)) != 0
     }
@@ -1,7 +1,7 @@
 use crate::{
     flags::{PatchFlag, RuntimeHelper, SlotFlag, StaticLevel},
     util::VStr,
-    Name,
+    Name, SourceLocation,
 };
 use rustc_hash::FxHashSet;
 use std::hash::Hash;
@@ -9,6 +9,9 @@ use std::hash::Hash;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+pub mod dump;
+pub mod walk;
+
 #[cfg(feature = "serde")]
 pub trait ConvertInfo {
     type TopType: Default + Serialize;
@@ -50,6 +53,8 @@ pub enum IRNode<T: ConvertInfo> {
     CacheNode(CacheIR<T>),
     /// comment
     CommentCall(T::CommentType),
+    /// reference to the static node moved to `hoists` at the index
+    Hoisted(usize),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -112,6 +117,8 @@ pub struct VNodeIR<T: ConvertInfo> {
     pub is_block: bool,
     pub disable_tracking: bool,
     pub is_component: bool,
+    /// template location of the element, default for synthesized vnodes
+    pub location: SourceLocation,
 }
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Slot<T: ConvertInfo> {
@@ -230,6 +237,10 @@ impl<'a> JsExpr<'a> {
             cache: false,
         }
     }
+    /// spread element in array or call args, e.g. `[a, ...b]`
+    pub fn spread(v: Self) -> Self {
+        JsExpr::Compound(vec![JsExpr::Src("..."), v])
+    }
     pub fn static_level(&self) -> StaticLevel {
         use JsExpr::*;
         use StaticLevel as S;
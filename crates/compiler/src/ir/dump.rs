@@ -0,0 +1,221 @@
+//! Indented textual tree of IR for debugging codegen issues.
+//! The output is meant for humans and has no stability guarantee.
+use super::{CacheKind, IRNode, IRRoot, JsExpr as Js, RuntimeDir, Slot};
+use crate::converter::{BaseConvertInfo, BaseIR};
+use std::fmt::Write;
+
+pub fn dump_ir(root: &IRRoot<BaseConvertInfo>) -> String {
+    let mut dumper = Dumper::default();
+    dumper.line(format_args!("IRRoot"));
+    dumper.children(&root.body);
+    dumper.out
+}
+
+#[derive(Default)]
+struct Dumper {
+    out: String,
+    indent: usize,
+}
+
+impl Dumper {
+    fn line(&mut self, content: std::fmt::Arguments) {
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+        self.out
+            .write_fmt(content)
+            .expect("string should never fail");
+        self.out.push('\n');
+    }
+    fn nested<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.indent += 1;
+        f(self);
+        self.indent -= 1;
+    }
+    fn children(&mut self, children: &[BaseIR]) {
+        self.nested(|d| children.iter().for_each(|c| d.ir(c)));
+    }
+    fn field(&mut self, name: &str, e: &Js) {
+        self.nested(|d| {
+            d.line(format_args!("{}:", name));
+            d.nested(|d| d.js(e));
+        });
+    }
+    fn opt_field(&mut self, name: &str, e: &Option<Js>) {
+        if let Some(e) = e {
+            self.field(name, e);
+        }
+    }
+
+    fn ir(&mut self, ir: &BaseIR) {
+        use IRNode as IR;
+        match ir {
+            IR::TextCall(t) => {
+                self.line(format_args!(
+                    "TextCall fast_path={} need_patch={}",
+                    t.fast_path, t.need_patch
+                ));
+                self.nested(|d| t.texts.iter().for_each(|e| d.js(e)));
+            }
+            IR::If(i) => {
+                self.line(format_args!("If"));
+                self.nested(|d| {
+                    for branch in i.branches.iter() {
+                        d.line(format_args!("IfBranch {}", branch.info));
+                        d.opt_field("condition", &branch.condition);
+                        d.nested(|d| d.ir(&branch.child));
+                    }
+                });
+            }
+            IR::For(f) => {
+                self.line(format_args!(
+                    "For is_stable={} fragment_flag={:?}",
+                    f.is_stable, f.fragment_flag
+                ));
+                self.field("source", &f.source);
+                let p = &f.parse_result;
+                self.field("value", &p.value);
+                self.opt_field("key", &p.key);
+                self.opt_field("index", &p.index);
+                self.opt_field("v_for_key", &f.key);
+                self.nested(|d| d.ir(&f.child));
+            }
+            IR::VNodeCall(v) => {
+                self.line(format_args!(
+                    "VNodeCall is_block={} is_component={} patch_flag={:?}",
+                    v.is_block, v.is_component, v.patch_flag
+                ));
+                self.field("tag", &v.tag);
+                self.opt_field("props", &v.props);
+                if !v.dynamic_props.is_empty() {
+                    let mut names: Vec<_> = v.dynamic_props.iter().map(|p| p.raw).collect();
+                    names.sort_unstable();
+                    self.nested(|d| d.line(format_args!("dynamic_props: {:?}", names)));
+                }
+                self.nested(|d| v.directives.iter().for_each(|dir| d.dir(dir)));
+                self.children(&v.children);
+            }
+            IR::RenderSlotCall(r) => {
+                self.line(format_args!("RenderSlotCall no_slotted={}", r.no_slotted));
+                self.field("slot_obj", &r.slot_obj);
+                self.field("slot_name", &r.slot_name);
+                self.opt_field("slot_props", &r.slot_props);
+                self.children(&r.fallbacks);
+            }
+            IR::VSlotUse(s) => {
+                self.line(format_args!("VSlotUse slot_flag={:?}", s.slot_flag));
+                self.nested(|d| s.stable_slots.iter().for_each(|s| d.slot(s)));
+                self.children(&s.alterable_slots);
+            }
+            IR::AlterableSlot(s) => {
+                self.line(format_args!("AlterableSlot"));
+                self.nested(|d| d.slot(s));
+            }
+            IR::CacheNode(c) => {
+                match &c.kind {
+                    CacheKind::Once => self.line(format_args!("CacheNode Once")),
+                    CacheKind::Memo(e) => {
+                        self.line(format_args!("CacheNode Memo"));
+                        self.field("memo", e);
+                    }
+                    CacheKind::MemoInVFor { v_for_key, expr } => {
+                        self.line(format_args!("CacheNode MemoInVFor"));
+                        self.opt_field("v_for_key", v_for_key);
+                        self.field("memo", expr);
+                    }
+                }
+                self.nested(|d| d.ir(&c.child));
+            }
+            IR::CommentCall(c) => self.line(format_args!("CommentCall {:?}", c)),
+            IR::Hoisted(i) => self.line(format_args!("Hoisted {}", i)),
+        }
+    }
+
+    fn dir(&mut self, dir: &RuntimeDir<BaseConvertInfo>) {
+        self.line(format_args!("RuntimeDir"));
+        self.field("name", &dir.name);
+        self.opt_field("expr", &dir.expr);
+        self.opt_field("arg", &dir.arg);
+        self.opt_field("mods", &dir.mods);
+    }
+
+    fn slot(&mut self, s: &Slot<BaseConvertInfo>) {
+        self.line(format_args!("Slot"));
+        self.field("name", &s.name);
+        self.opt_field("param", &s.param);
+        self.children(&s.body);
+    }
+
+    fn js(&mut self, e: &Js) {
+        match e {
+            Js::Src(s) => self.line(format_args!("Src {:?}", s)),
+            Js::Num(n) => self.line(format_args!("Num {}", n)),
+            Js::StrLit(v) => self.line(format_args!("StrLit {:?}", v.raw)),
+            Js::Simple(v, _) => self.line(format_args!("Simple {:?}", v.into_string())),
+            Js::Param(p) => self.line(format_args!("Param {:?}", p)),
+            Js::FuncSimple { src, cache, .. } => {
+                self.line(format_args!("FuncSimple {:?} cache={}", src.raw, cache))
+            }
+            Js::FuncCompound { body, cache, .. } => {
+                self.line(format_args!("FuncCompound cache={}", cache));
+                self.nested(|d| body.iter().for_each(|e| d.js(e)));
+            }
+            Js::Compound(es) => {
+                self.line(format_args!("Compound"));
+                self.nested(|d| es.iter().for_each(|e| d.js(e)));
+            }
+            Js::Props(ps) => {
+                self.line(format_args!("Props"));
+                self.nested(|d| {
+                    for (key, val) in ps.iter() {
+                        d.line(format_args!("Prop"));
+                        d.nested(|d| {
+                            d.js(key);
+                            d.js(val);
+                        });
+                    }
+                });
+            }
+            Js::Call(rh, args) => {
+                self.line(format_args!("Call {}", rh.helper_str(&[])));
+                self.nested(|d| args.iter().for_each(|e| d.js(e)));
+            }
+            Js::Symbol(rh) => self.line(format_args!("Symbol {}", rh.helper_str(&[]))),
+            Js::Array(es) => {
+                self.line(format_args!("Array"));
+                self.nested(|d| es.iter().for_each(|e| d.js(e)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::converter::test::base_convert;
+
+    #[test]
+    fn test_dump_ir() {
+        let ir = base_convert("<div :id='a'><p v-if='b'>{{c}}</p><span v-for='i in d'/></div>");
+        let s = dump_ir(&ir);
+        assert!(s.starts_with("IRRoot\n  VNodeCall"), "{}", s);
+        for label in [
+            "tag:",
+            "StrLit \"div\"",
+            "props:",
+            "Props",
+            "Simple \"a\"",
+            "If",
+            "IfBranch 0",
+            "condition:",
+            "Call toDisplayString",
+            "For ",
+            "source:",
+            "Param \"i\"",
+        ] {
+            assert!(s.contains(label), "{} not in\n{}", label, s);
+        }
+        // children are indented under their parent
+        assert!(s.contains("\n    If\n"), "{}", s);
+    }
+}
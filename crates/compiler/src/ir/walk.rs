@@ -0,0 +1,163 @@
+//! Read only depth-first traversal of IR for external analysis,
+//! e.g. linting or metrics. Parent is visited before its children.
+//! Transform passes mutate IR and should use CorePass instead.
+use super::{CacheKind, IRNode, JsExpr as Js, RuntimeDir, Slot};
+use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot};
+
+pub trait IRVisitor<'a> {
+    fn visit_ir(&mut self, _: &BaseIR<'a>) {}
+    /// called for every expression, nested ones included
+    fn visit_js_expr(&mut self, _: &Js<'a>) {}
+}
+
+pub fn walk<'a, V: IRVisitor<'a>>(root: &BaseRoot<'a>, v: &mut V) {
+    walk_children(&root.body, v);
+}
+
+pub fn walk_ir<'a, V: IRVisitor<'a>>(ir: &BaseIR<'a>, v: &mut V) {
+    use IRNode as IR;
+    v.visit_ir(ir);
+    match ir {
+        IR::TextCall(t) => t.texts.iter().for_each(|e| walk_js(e, v)),
+        IR::If(i) => {
+            for branch in i.branches.iter() {
+                walk_opt_js(&branch.condition, v);
+                walk_ir(&branch.child, v);
+            }
+        }
+        IR::For(f) => {
+            walk_js(&f.source, v);
+            let p = &f.parse_result;
+            walk_js(&p.value, v);
+            walk_opt_js(&p.key, v);
+            walk_opt_js(&p.index, v);
+            walk_opt_js(&f.key, v);
+            walk_ir(&f.child, v);
+        }
+        IR::VNodeCall(vn) => {
+            walk_js(&vn.tag, v);
+            walk_opt_js(&vn.props, v);
+            walk_children(&vn.children, v);
+            vn.directives.iter().for_each(|d| walk_dir(d, v));
+        }
+        IR::RenderSlotCall(r) => {
+            walk_js(&r.slot_obj, v);
+            walk_js(&r.slot_name, v);
+            walk_opt_js(&r.slot_props, v);
+            walk_children(&r.fallbacks, v);
+        }
+        IR::VSlotUse(s) => {
+            s.stable_slots.iter().for_each(|f| walk_slot(f, v));
+            walk_children(&s.alterable_slots, v);
+        }
+        IR::AlterableSlot(f) => walk_slot(f, v),
+        IR::CacheNode(c) => {
+            match &c.kind {
+                CacheKind::Once => (),
+                CacheKind::Memo(e) => walk_js(e, v),
+                CacheKind::MemoInVFor { v_for_key, expr } => {
+                    walk_opt_js(v_for_key, v);
+                    walk_js(expr, v);
+                }
+            }
+            walk_ir(&c.child, v);
+        }
+        IR::CommentCall(_) | IR::Hoisted(_) => (),
+    }
+}
+
+pub fn walk_js<'a, V: IRVisitor<'a>>(e: &Js<'a>, v: &mut V) {
+    v.visit_js_expr(e);
+    match e {
+        Js::Src(_)
+        | Js::Num(_)
+        | Js::StrLit(_)
+        | Js::Simple(..)
+        | Js::Param(_)
+        | Js::FuncSimple { .. }
+        | Js::Symbol(_) => (),
+        Js::FuncCompound { body: es, .. } | Js::Compound(es) | Js::Call(_, es) | Js::Array(es) => {
+            es.iter().for_each(|e| walk_js(e, v))
+        }
+        Js::Props(ps) => {
+            for (key, val) in ps.iter() {
+                walk_js(key, v);
+                walk_js(val, v);
+            }
+        }
+    }
+}
+
+fn walk_children<'a, V: IRVisitor<'a>>(children: &[BaseIR<'a>], v: &mut V) {
+    children.iter().for_each(|c| walk_ir(c, v));
+}
+
+fn walk_opt_js<'a, V: IRVisitor<'a>>(e: &Option<Js<'a>>, v: &mut V) {
+    if let Some(e) = e {
+        walk_js(e, v);
+    }
+}
+
+fn walk_dir<'a, V: IRVisitor<'a>>(d: &RuntimeDir<BaseConvertInfo<'a>>, v: &mut V) {
+    walk_js(&d.name, v);
+    walk_opt_js(&d.expr, v);
+    walk_opt_js(&d.arg, v);
+    walk_opt_js(&d.mods, v);
+}
+
+fn walk_slot<'a, V: IRVisitor<'a>>(s: &Slot<BaseConvertInfo<'a>>, v: &mut V) {
+    walk_js(&s.name, v);
+    walk_opt_js(&s.param, v);
+    walk_children(&s.body, v);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::converter::test::base_convert;
+
+    #[derive(Default)]
+    struct Counter {
+        vnode: usize,
+        text: usize,
+        v_if: usize,
+        v_for: usize,
+        slot_fn: usize,
+        comment: usize,
+        js: usize,
+    }
+    impl<'a> IRVisitor<'a> for Counter {
+        fn visit_ir(&mut self, ir: &BaseIR<'a>) {
+            match ir {
+                IRNode::VNodeCall(_) => self.vnode += 1,
+                IRNode::TextCall(_) => self.text += 1,
+                IRNode::If(_) => self.v_if += 1,
+                IRNode::For(_) => self.v_for += 1,
+                IRNode::VSlotUse(_) => self.slot_fn += 1,
+                IRNode::CommentCall(_) => self.comment += 1,
+                _ => (),
+            }
+        }
+        fn visit_js_expr(&mut self, _: &Js<'a>) {
+            self.js += 1;
+        }
+    }
+
+    #[test]
+    fn test_count_nodes() {
+        let ir = base_convert(
+            "<div><p v-if='a'>{{b}}</p><span v-for='i in list'/><!--c--><comp>d</comp></div>",
+        );
+        let mut counter = Counter::default();
+        walk(&ir, &mut counter);
+        // div, p, span, comp
+        assert_eq!(counter.vnode, 4);
+        // {{b}} and d
+        assert_eq!(counter.text, 2);
+        assert_eq!(counter.v_if, 1);
+        assert_eq!(counter.v_for, 1);
+        assert_eq!(counter.slot_fn, 1);
+        assert_eq!(counter.comment, 1);
+        assert!(counter.js > 0);
+    }
+}
@@ -89,7 +89,7 @@ pub enum Namespace {
     UserDefined(&'static str),
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Hash)]
 pub enum BindingTypes {
     /// returned from data()
     Data,
@@ -105,8 +105,13 @@ pub enum BindingTypes {
     SetupMaybeRef,
     /// bindings that are guaranteed to be refs
     SetupRef,
+    /// a ref declared by defineModel, v-model writes to its value
+    SetupModel,
     /// declared by other options, e.g. computed, inject
     Options,
+    /// a const binding initialized by a string literal, e.g. `const a = 'str'`.
+    /// Interpolating it needs no toDisplayString.
+    LiteralString,
 }
 
 impl BindingTypes {
@@ -220,7 +220,7 @@ pub struct AstRoot<'a> {
     pub location: SourceLocation,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 pub enum WhitespaceStrategy {
     Preserve,
     Condense,
@@ -446,6 +446,7 @@ where
         if is_v_pre_boundary(elem) {
             debug_assert!(self.v_pre_index.is_none());
             self.v_pre_index = Some(self.open_elems.len());
+            self.tokens.set_is_in_v_pre(true);
         }
     }
     fn parse_end_tag(&mut self, end_tag: &'a str) {
@@ -511,6 +512,7 @@ where
         // met v-pre boundary, switch back
         if idx == self.open_elems.len() {
             self.v_pre_index = None;
+            self.tokens.set_is_in_v_pre(false);
         }
     }
     fn parse_element(&mut self, mut elem: Element<'a>) -> AstNode<'a> {
@@ -851,27 +853,22 @@ fn compress_whitespaces(nodes: &mut Vec<AstNode>, need_condense: bool) {
     let mut i = 0;
     while i < nodes.len() {
         let should_remove = if let AstNode::Text(child) = &nodes[i] {
-            use AstNode as A;
             if !child.is_all_whitespace() {
                 // non empty text node
                 if need_condense {
                     compress_text_node(&mut nodes[i]);
                 }
                 false
-            } else if i == nodes.len() - 1 || i == 0 {
-                // Remove the leading/trailing whitespace
-                true
-            } else if !need_condense {
-                false
             } else {
-                // Condense mode remove whitespaces between comment and
-                // whitespaces with contains newline between two elements
-                let prev = &nodes[i - 1];
-                let next = &nodes[i + 1];
-                match (prev, next) {
-                    (A::Comment(_), A::Comment(_)) => true,
-                    _ => is_element(prev) && is_element(next) && child.contains(&['\r', '\n'][..]),
+                // Remove the leading/trailing whitespace
+                let is_edge = i == 0 || i == nodes.len() - 1;
+                let removable = is_edge
+                    || (need_condense && is_condensable(&nodes[i - 1], &nodes[i + 1], child));
+                if !removable {
+                    // kept whitespaces are condensed into a single space in both modes
+                    compress_text_node(&mut nodes[i]);
                 }
+                removable
             }
         } else {
             false
@@ -884,6 +881,16 @@ fn compress_whitespaces(nodes: &mut Vec<AstNode>, need_condense: bool) {
     }
 }
 
+// Condense mode remove whitespaces between comment and
+// whitespaces with contains newline between two elements
+fn is_condensable(prev: &AstNode, next: &AstNode, text: &TextNode) -> bool {
+    use AstNode as A;
+    match (prev, next) {
+        (A::Comment(_), A::Comment(_)) => true,
+        _ => is_element(prev) && is_element(next) && text.contains(&['\r', '\n'][..]),
+    }
+}
+
 #[inline]
 fn is_element(n: &AstNode) -> bool {
     n.get_element().is_some()
@@ -954,6 +961,25 @@ pub mod test {
         let val = decode.value.unwrap().content;
         assert_eq!(val.into_string(), "&");
     }
+    #[test]
+    fn test_v_pre_interpolation() {
+        let case = "<div v-pre>{{ a }}<p :b='c'>{{d}}</p></div>{{e}}";
+        let ast = base_parse(case);
+        let mut children = ast.children;
+        assert_eq!(children.len(), 2);
+        let e = children.pop().unwrap();
+        let v = cast!(e, AstNode::Interpolation);
+        assert_eq!(v.source, "e");
+        let mut div = children.pop().unwrap().into_element();
+        assert_eq!(div.children.len(), 2);
+        let p = div.children.pop().unwrap().into_element();
+        let b = cast!(&p.properties[0], ElemProp::Attr);
+        assert_eq!(b.name, ":b");
+        let d = cast!(&p.children[0], AstNode::Text);
+        assert_eq!(d.text[0].raw, "{{d}}");
+        let a = cast!(&div.children[0], AstNode::Text);
+        assert_eq!(a.text[0].raw, "{{ a }}");
+    }
 
     pub fn base_parse(s: &str) -> AstRoot {
         let tokens = base_scan(s);
@@ -99,6 +99,12 @@ pub trait FlagCDataNs {
     fn need_flag_hint(&self) -> bool;
 }
 
+/// Parser tells scanner whether it is inside a v-pre subtree.
+/// Interpolation delimiters in v-pre are plain text and must be kept verbatim.
+pub trait FlagVPre {
+    fn set_is_in_v_pre(&mut self, flag: bool);
+}
+
 /// This trait produces a compiler's current position and selects a range.
 pub trait Locatable {
     /// Returns the scanner's current position in the source.
@@ -150,6 +156,7 @@ impl Scanner {
             option: self.option.clone(),
             last_start_tag_name: None,
             is_in_html_namespace: true,
+            is_in_v_pre: false,
             delimiter_first_char: self.delimiter_first_char,
         }
     }
@@ -169,6 +176,8 @@ pub struct Tokens<'a> {
     last_start_tag_name: Option<&'a str>,
     // this flag is for handling CDATA in non HTML namespace.
     is_in_html_namespace: bool,
+    // this flag is for skipping interpolation in v-pre.
+    is_in_v_pre: bool,
     delimiter_first_char: char,
 }
 
@@ -183,6 +192,13 @@ impl<'a> Tokens<'a> {
     fn scan_data(&mut self) -> Token<'a> {
         debug_assert!(self.mode == TextMode::Data);
         debug_assert!(!self.source.is_empty());
+        if self.is_in_v_pre {
+            return match self.source.find('<') {
+                Some(0) => self.scan_tag_open(),
+                Some(i) => self.scan_text(i),
+                None => self.scan_text(self.source.len()),
+            };
+        }
         let d = self.delimiter_first_char;
         let mut offset = 0;
         // process html entity & later
@@ -790,6 +806,12 @@ impl<'a> FlagCDataNs for Tokens<'a> {
     }
 }
 
+impl<'a> FlagVPre for Tokens<'a> {
+    fn set_is_in_v_pre(&mut self, in_v_pre: bool) {
+        self.is_in_v_pre = in_v_pre;
+    }
+}
+
 impl<'a> Locatable for Tokens<'a> {
     fn current_position(&self) -> Position {
         self.position.clone()
@@ -807,7 +829,10 @@ impl<'a> Locatable for Tokens<'a> {
     }
 }
 
-pub trait TokenSource<'a>: FusedIterator<Item = Token<'a>> + FlagCDataNs + Locatable {}
+pub trait TokenSource<'a>:
+    FusedIterator<Item = Token<'a>> + FlagCDataNs + FlagVPre + Locatable
+{
+}
 impl<'a> TokenSource<'a> for Tokens<'a> {}
 
 #[cfg(test)]
@@ -143,6 +143,18 @@ mod test {
         assert!(helpers.contains(RH::CREATE_COMMENT));
     }
     #[test]
+    fn test_static_helper() {
+        let ir = transform("hello world");
+        assert!(ir.top_scope.helpers.is_empty());
+        let ir = transform("<p>hello</p>");
+        let helpers = ir.top_scope.helpers;
+        assert!(helpers.contains(RH::CREATE_ELEMENT_VNODE));
+        assert!(!helpers.contains(RH::TO_DISPLAY_STRING));
+        let ir = transform("<p>{{a}}</p>");
+        let helpers = ir.top_scope.helpers;
+        assert!(helpers.contains(RH::TO_DISPLAY_STRING));
+    }
+    #[test]
     fn test_v_for_helper() {
         let ir = transform("<p v-for='a in b'/>");
         let helpers = ir.top_scope.helpers;
@@ -3,10 +3,11 @@
 /// components. NB. requires prefix_identifiers
 use super::{BaseInfo, BaseVNode, CorePassExt, BaseCache, Scope};
 use crate::{
-    converter::v_on::get_handler_type,
-    flags::StaticLevel,
-    ir::{JsExpr as Js, CacheKind, HandlerType},
+    converter::{v_on::get_handler_type, BaseIR, BaseRoot},
+    flags::{PatchFlag, StaticLevel},
+    ir::{JsExpr as Js, CacheKind, HandlerType, IRNode},
 };
+use std::mem;
 
 // 1. cache handler
 // 2. hoist static
@@ -14,18 +15,30 @@ pub struct HoistStatic {
     in_v_once: bool,
     is_component: bool,
     cache_handlers: bool,
+    hoist_static: bool,
 }
 impl HoistStatic {
-    pub fn new(cache_handlers: bool) -> Self {
+    pub fn new(cache_handlers: bool, hoist_static: bool) -> Self {
         Self {
             in_v_once: false,
             is_component: false,
             cache_handlers,
+            hoist_static,
         }
     }
 }
 
 impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic {
+    // hoist after all passes are done with the node and its expressions
+    fn exit_root(&mut self, r: &mut BaseRoot<'a>, _: &mut Scope<'a>) {
+        if !self.hoist_static {
+            return;
+        }
+        let hoists = &mut r.top_scope.hoists;
+        for child in r.body.iter_mut() {
+            hoist_in_ir(child, hoists);
+        }
+    }
     fn enter_cache(&mut self, cn: &mut BaseCache<'a>, _: &mut Scope<'a>) {
         if matches!(cn.kind, CacheKind::Once) {
             self.in_v_once = true;
@@ -71,3 +84,74 @@ impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic {
         *cache = should_cache;
     }
 }
+
+fn hoist_in_ir<'a>(ir: &mut BaseIR<'a>, hoists: &mut Vec<BaseIR<'a>>) {
+    use IRNode as IR;
+    match ir {
+        // component children are slots, their body is walked below
+        IR::VNodeCall(v) if !v.is_component => hoist_children(&mut v.children, hoists),
+        IR::VNodeCall(v) => hoist_in_list(&mut v.children, hoists),
+        IR::If(i) => {
+            for branch in i.branches.iter_mut() {
+                hoist_in_ir(&mut branch.child, hoists);
+            }
+        }
+        IR::For(f) => hoist_in_ir(&mut f.child, hoists),
+        IR::RenderSlotCall(r) => hoist_in_list(&mut r.fallbacks, hoists),
+        IR::VSlotUse(s) => {
+            for slot in s.stable_slots.iter_mut() {
+                hoist_in_list(&mut slot.body, hoists);
+            }
+            hoist_in_list(&mut s.alterable_slots, hoists);
+        }
+        IR::AlterableSlot(s) => hoist_in_list(&mut s.body, hoists),
+        // v-once/v-memo subtree is already cached
+        IR::CacheNode(_) => (),
+        IR::TextCall(_) | IR::CommentCall(_) | IR::Hoisted(_) => (),
+    }
+}
+
+fn hoist_in_list<'a>(list: &mut [BaseIR<'a>], hoists: &mut Vec<BaseIR<'a>>) {
+    for ir in list.iter_mut() {
+        hoist_in_ir(ir, hoists);
+    }
+}
+
+/// replace static element children with references to hoisted nodes
+fn hoist_children<'a>(children: &mut [BaseIR<'a>], hoists: &mut Vec<BaseIR<'a>>) {
+    for child in children.iter_mut() {
+        if !is_static_node(child) {
+            hoist_in_ir(child, hoists);
+            continue;
+        }
+        let mut hoisted = mem::replace(child, IRNode::Hoisted(hoists.len()));
+        if let IRNode::VNodeCall(v) = &mut hoisted {
+            v.patch_flag = PatchFlag::HOISTED;
+        }
+        hoists.push(hoisted);
+    }
+}
+
+fn is_static_node(ir: &BaseIR) -> bool {
+    match ir {
+        IRNode::VNodeCall(v) => is_static_vnode(v),
+        _ => false,
+    }
+}
+
+fn is_static_vnode(v: &BaseVNode) -> bool {
+    let can_hoist = |e: &Js| e.static_level() >= StaticLevel::CanHoist;
+    !v.is_component
+        && !v.is_block
+        && v.patch_flag.is_empty()
+        && v.dynamic_props.is_empty()
+        && v.directives.is_empty()
+        && can_hoist(&v.tag)
+        && v.props.as_ref().map_or(true, can_hoist)
+        && v.children.iter().all(|child| match child {
+            IRNode::VNodeCall(v) => is_static_vnode(v),
+            IRNode::TextCall(t) => t.texts.iter().all(can_hoist),
+            IRNode::CommentCall(_) => true,
+            _ => false,
+        })
+}
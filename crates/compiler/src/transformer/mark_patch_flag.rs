@@ -17,15 +17,19 @@ impl<'a> CorePass<BaseInfo<'a>> for PatchFlagMarker {
                     vn.is_block = true;
                 }
             }
+            // NB: continue, not return. Other branches still need keys.
             let props = match &mut *branch.child {
                 IR::VNodeCall(v) => &mut v.props,
                 IR::RenderSlotCall(r) => &mut r.slot_props,
-                IR::For(f) => return f.key = Some(Js::Num(branch.info)),
-                _ => return,
+                IR::For(f) => {
+                    f.key = Some(Js::Num(branch.info));
+                    continue;
+                }
+                _ => continue,
             };
             // already has key
             if props.as_ref().map_or(false, find_key_on_js) {
-                return;
+                continue;
             }
             // inject default key
             let key = Js::str_lit("key");
@@ -88,13 +92,20 @@ fn find_prop<'a, 'b>(t: &'b BaseIR<'a>) -> Option<&'b Js<'a>> {
         IR::VSlotUse(_) => {
             panic!("v-slot with v-for must be alterable slots")
         }
-        IR::TextCall(_) | IR::For(_) | IR::If(_) | IR::CommentCall(_) => {
+        IR::TextCall(_) | IR::For(_) | IR::If(_) | IR::CommentCall(_) | IR::Hoisted(_) => {
             panic!("v-for child must be vnode/renderSlot/slotfn")
         }
     }
 }
 
 fn find_key(t: &BaseIR) -> bool {
+    // v-if on the v-for element has default key injected, only user key counts
+    if let IR::If(i) = t {
+        return i
+            .branches
+            .iter()
+            .any(|b| find_prop(&b.child).map_or(false, find_user_key));
+    }
     let props = find_prop(t);
     if let Some(prop) = props {
         find_key_on_js(prop)
@@ -114,6 +125,18 @@ fn find_key_on_js(e: &Js) -> bool {
     }
 }
 
+/// default key injected by v-if is a number, which users cannot write
+fn find_user_key(e: &Js) -> bool {
+    match e {
+        Js::Call(RH::MERGE_PROPS, args) => args.iter().any(find_user_key),
+        Js::Props(ps) => ps.iter().any(|(k, v)| match k {
+            Js::StrLit(s) => s.raw == "key" && !matches!(v, Js::Num(_)),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
 // 1. mergeProps(...)
 // 2. toHandlers(...)
 fn inject_prop<'a>(props: &mut Js<'a>, key: Prop<'a>) {
@@ -140,3 +163,41 @@ fn inject_prop<'a>(props: &mut Js<'a>, key: Prop<'a>) {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::test::base_convert;
+    use super::*;
+    use crate::cast;
+    use crate::transformer::{BaseTransformer, Transformer};
+
+    fn transform(s: &str) -> Vec<BaseIR> {
+        let mut ir = base_convert(s);
+        BaseTransformer::transform(&mut ir, PatchFlagMarker);
+        ir.body
+    }
+
+    #[test]
+    fn test_if_fragment_branches() {
+        let body =
+            transform("<template v-if='a'><a/><b/></template><template v-else><c/></template>");
+        let i = cast!(&body[0], IR::If);
+        for (n, branch) in i.branches.iter().enumerate() {
+            let vn = cast!(&*branch.child, IR::VNodeCall);
+            assert!(matches!(vn.tag, Js::Symbol(RH::FRAGMENT)));
+            assert!(vn.is_block);
+            assert!(vn.patch_flag.contains(PatchFlag::STABLE_FRAGMENT));
+            assert_eq!(branch.info, n);
+            assert!(find_key_on_js(vn.props.as_ref().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_key_after_non_vnode_branch() {
+        let body = transform("<p v-if='a' v-once/><p v-else/>");
+        let i = cast!(&body[0], IR::If);
+        let vn = cast!(&*i.branches[1].child, IR::VNodeCall);
+        assert!(vn.is_block);
+        assert!(find_key_on_js(vn.props.as_ref().unwrap()));
+    }
+}
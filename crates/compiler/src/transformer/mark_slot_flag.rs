@@ -9,6 +9,10 @@ impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for SlotFlagMarker {
         if !v.is_component || v.children.is_empty() {
             return;
         }
+        // Teleport and KeepAlive are compiled with raw children instead of slots
+        if !matches!(v.children[0], IRNode::VSlotUse(_)) {
+            return;
+        }
         debug_assert_eq!(v.children.len(), 1);
         let has_dynamic_slots = scope.has_ref_in_vnode(v);
         // has dynamic stable slot key
@@ -56,6 +60,7 @@ fn has_forward_one(ir: &BaseIR) -> bool {
         IR::AlterableSlot(s) => has_forward_list(&s.body),
         IR::TextCall(_) => false,
         IR::CommentCall(_) => false,
+        IR::Hoisted(_) => false,
     }
 }
 
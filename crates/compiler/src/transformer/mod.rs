@@ -76,6 +76,7 @@ trait CoreTransformer<T: ConvertInfo, P: CorePass<T>>: Transformer<P> {
             I::VSlotUse(s) => Self::transform_v_slot(s, ps),
             I::CacheNode(c) => Self::transform_cache(c, ps),
             I::AlterableSlot(a) => Self::transform_slot_fn(a, ps),
+            I::Hoisted(_) => (),
         }
     }
     fn transform_children(children: &mut Vec<IRNode<T>>, ps: &mut P) {
@@ -1,9 +1,11 @@
 /// extract class/style for faster runtime patching
-use crate::ir::JsExpr as Js;
+use crate::converter::{BaseIR, BaseRoot};
+use crate::ir::{ForParseResult, IRNode, JsExpr as Js};
 use crate::flags::RuntimeHelper as RH;
-use super::{BaseInfo, BaseVNode, CorePass};
+use crate::util::{is_reserved_prop, is_simple_identifier, rslint, VStr};
+use super::{BaseCache, BaseFor, BaseIf, BaseInfo, BaseRenderSlot, BaseSlotFn, BaseVNode, CorePass};
 
-use std::mem;
+use std::{iter, mem};
 
 pub struct NormalizeProp;
 
@@ -14,7 +16,8 @@ impl<'a> CorePass<BaseInfo<'a>> for NormalizeProp {
             None => return,
         };
         match props {
-            Js::Call(..) => (), // nothing! MergeProps/toHandlers call
+            Js::Call(RH::MERGE_PROPS, args) => guard_single_v_bind(args),
+            Js::Call(..) => (), // nothing! toHandlers call
             Js::Props(ps) => {
                 let ps = mem::take(ps);
                 *props = pre_normalize_prop(ps);
@@ -30,10 +33,161 @@ impl<'a> CorePass<BaseInfo<'a>> for NormalizeProp {
     }
 }
 
+/// Normalize class of a v-for element once before render returns if it does
+/// not use the loop aliases, e.g. `<p v-for="item in list" :class="cls"/>`.
+/// Function calls are kept per item since they may return different values.
+/// Only v-for always evaluated by render is handled, not the ones in v-if,
+/// v-for, slots or cache, whose class may be invalid outside of them.
+#[derive(Default)]
+pub struct HoistForClass<'a> {
+    /// if each entered v-for, outer to inner, can hoist its class
+    hoistable: Vec<bool>,
+    /// depth of v-if, v-for, slot and cache nodes around current node
+    guards: usize,
+    classes: Vec<Js<'a>>,
+}
+
+impl<'a> CorePass<BaseInfo<'a>> for HoistForClass<'a> {
+    fn exit_root(&mut self, r: &mut BaseRoot<'a>) {
+        r.top_scope.classes.append(&mut self.classes);
+    }
+    // class is checked as written, before prefixing and normalizing
+    fn enter_for(&mut self, f: &mut BaseFor<'a>) {
+        let aliases = for_aliases(&f.parse_result);
+        let hoistable = match (aliases, find_class(&mut f.child)) {
+            (Some(aliases), Some(cls)) if self.guards == 0 => is_loop_invariant(cls, &aliases),
+            _ => false,
+        };
+        self.hoistable.push(hoistable);
+        self.guards += 1;
+    }
+    fn exit_for(&mut self, f: &mut BaseFor<'a>) {
+        self.guards -= 1;
+        if !self.hoistable.pop().unwrap_or(false) {
+            return;
+        }
+        let cls = match find_class(&mut f.child) {
+            Some(cls) if matches!(cls, Js::Call(RH::NORMALIZE_CLASS, _)) => cls,
+            _ => return,
+        };
+        // codegen declares the normalized class as _class, _class1...
+        let var = match self.classes.len() {
+            0 => Js::Src("_class"),
+            n => Js::Compound(vec![Js::Src("_class"), Js::Num(n)]),
+        };
+        let normalized = mem::replace(cls, var);
+        self.classes.push(normalized);
+    }
+    fn enter_if(&mut self, _: &mut BaseIf<'a>) {
+        self.guards += 1;
+    }
+    fn exit_if(&mut self, _: &mut BaseIf<'a>) {
+        self.guards -= 1;
+    }
+    fn enter_slot_outlet(&mut self, _: &mut BaseRenderSlot<'a>) {
+        self.guards += 1;
+    }
+    fn exit_slot_outlet(&mut self, _: &mut BaseRenderSlot<'a>) {
+        self.guards -= 1;
+    }
+    fn enter_slot_fn(&mut self, _: &mut BaseSlotFn<'a>) {
+        self.guards += 1;
+    }
+    fn exit_slot_fn(&mut self, _: &mut BaseSlotFn<'a>) {
+        self.guards -= 1;
+    }
+    fn enter_cache(&mut self, _: &mut BaseCache<'a>) {
+        self.guards += 1;
+    }
+    fn exit_cache(&mut self, _: &mut BaseCache<'a>) {
+        self.guards -= 1;
+    }
+}
+
+/// None if an alias is not broken down to identifiers, e.g. without prefixing
+fn for_aliases<'a>(p: &ForParseResult<BaseInfo<'a>>) -> Option<Vec<&'a str>> {
+    let mut aliases = vec![];
+    for alias in iter::once(&p.value).chain(&p.key).chain(&p.index) {
+        match alias {
+            Js::Param("") => (), // skipped alias like `(v, , i)`
+            Js::Param(id) if is_simple_identifier(VStr::raw(id)) => aliases.push(*id),
+            Js::Compound(ids) => aliases.extend(ids.iter().filter_map(|id| match id {
+                Js::Param(id) => Some(*id),
+                _ => None,
+            })),
+            _ => return None,
+        }
+    }
+    Some(aliases)
+}
+
+/// class prop of the element with v-for, not the ones of its children
+fn find_class<'a, 'b>(child: &'b mut BaseIR<'a>) -> Option<&'b mut Js<'a>> {
+    let props = match child {
+        IRNode::VNodeCall(v) => v.props.as_mut()?,
+        _ => return None,
+    };
+    let props = match props {
+        Js::Props(ps) => ps,
+        _ => return None,
+    };
+    props
+        .iter_mut()
+        .find(|(k, _)| matches!(k, Js::StrLit(k) if k.raw == "class"))
+        .map(|(_, v)| v)
+}
+
+fn is_loop_invariant(e: &Js, aliases: &[&str]) -> bool {
+    match e {
+        Js::StrLit(_) | Js::Num(_) | Js::Src(_) => true,
+        Js::Simple(v, _) => {
+            let expr = match rslint::parse_js_expr(v.raw) {
+                Some(expr) if !rslint::has_call_expr(&expr) => expr,
+                _ => return false,
+            };
+            let mut uses_alias = false;
+            rslint::walk_free_variables(expr, |fv| {
+                uses_alias |= aliases.contains(&fv.text().as_str());
+            });
+            !uses_alias
+        }
+        Js::Compound(v) | Js::Array(v) => v.iter().all(|e| is_loop_invariant(e, aliases)),
+        Js::Props(ps) => ps
+            .iter()
+            .all(|(k, v)| is_loop_invariant(k, aliases) && is_loop_invariant(v, aliases)),
+        _ => false,
+    }
+}
+
 fn is_handler_expr(j: &Js) -> bool {
     matches!(j, Js::FuncSimple { .. }) || matches!(j, Js::FuncCompound { .. })
 }
 
+/// <comp v-bind="obj" key="k"/> merges obj with reserved props only.
+/// Guard the lone v-bind object so reactive props are not passed raw.
+fn guard_single_v_bind(args: &mut [Js]) {
+    let only_reserved = |arg: &Js| match arg {
+        Js::Props(ps) => ps
+            .iter()
+            .all(|(k, _)| matches!(k, Js::StrLit(k) if is_reserved_prop(k))),
+        _ => true,
+    };
+    if !args.iter().all(only_reserved) {
+        return;
+    }
+    let mut dynamic = args.iter_mut().filter(|a| !matches!(a, Js::Props(..)));
+    let single_v_bind = match (dynamic.next(), dynamic.next()) {
+        (Some(e), None) => e,
+        _ => return,
+    };
+    // v-on="obj" is already wrapped in toHandlers
+    if matches!(single_v_bind, Js::Call(..)) {
+        return;
+    }
+    let e = mem::take(single_v_bind);
+    *single_v_bind = Js::Call(RH::GUARD_REACTIVE_PROPS, vec![e]);
+}
+
 fn pre_normalize_prop<'a>(mut props: Vec<(Js<'a>, Js<'a>)>) -> Js<'a> {
     let mut class_val = None;
     let mut style_val = None;
@@ -67,3 +221,104 @@ fn pre_normalize_prop<'a>(mut props: Vec<(Js<'a>, Js<'a>)>) -> Js<'a> {
     }
     Js::Props(props)
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::test::base_convert;
+    use super::*;
+    use crate::cast;
+    use crate::converter::BaseRoot;
+    use crate::ir::IRNode;
+    use crate::transformer::{BaseTransformer, Transformer};
+
+    fn get_merge_args<'a, 'b>(ir: &'b BaseRoot<'a>) -> &'b [Js<'a>] {
+        let vn = cast!(&ir.body[0], IRNode::VNodeCall);
+        match vn.props.as_ref().unwrap() {
+            Js::Call(RH::MERGE_PROPS, args) => args,
+            _ => panic!("props should be merged"),
+        }
+    }
+    #[test]
+    fn test_single_v_bind_with_key() {
+        let mut ir = base_convert("<comp v-bind='obj' key='k'/>");
+        BaseTransformer::transform(&mut ir, NormalizeProp);
+        let args = get_merge_args(&ir);
+        assert!(matches!(args[0], Js::Call(RH::GUARD_REACTIVE_PROPS, _)));
+        assert!(matches!(args[1], Js::Props(..)));
+    }
+    #[test]
+    fn test_merge_static_style() {
+        let mut ir = base_convert("<p style='color: red' :style='{ fontSize: x }'/>");
+        BaseTransformer::transform(&mut ir, NormalizeProp);
+        let vn = cast!(&ir.body[0], IRNode::VNodeCall);
+        let props = cast!(vn.props.as_ref().unwrap(), Js::Props);
+        let args = match &props[0].1 {
+            Js::Call(RH::NORMALIZE_STYLE, args) => args,
+            _ => panic!("style should be normalized"),
+        };
+        let arr = cast!(&args[0], Js::Array);
+        let static_style = cast!(&arr[0], Js::Props);
+        assert!(matches!(&static_style[0].0, Js::StrLit(k) if k.raw == "color"));
+        assert!(matches!(&static_style[0].1, Js::StrLit(v) if v.raw == "red"));
+        assert!(matches!(arr[1], Js::Simple(..)));
+    }
+    #[test]
+    fn test_merge_class() {
+        for src in ["<p class='a' :class='b'/>", "<p :class='b' class='a'/>"] {
+            let mut ir = base_convert(src);
+            BaseTransformer::transform(&mut ir, NormalizeProp);
+            let vn = cast!(&ir.body[0], IRNode::VNodeCall);
+            let props = cast!(vn.props.as_ref().unwrap(), Js::Props);
+            assert_eq!(props.len(), 1);
+            let args = match &props[0].1 {
+                Js::Call(RH::NORMALIZE_CLASS, args) => args,
+                _ => panic!("class should be normalized"),
+            };
+            let arr = cast!(&args[0], Js::Array);
+            assert_eq!(arr.len(), 2);
+            assert!(arr
+                .iter()
+                .any(|c| matches!(c, Js::StrLit(a) if a.raw == "a")));
+            assert!(arr
+                .iter()
+                .any(|c| matches!(c, Js::Simple(b, _) if b.raw == "b")));
+        }
+    }
+    #[test]
+    fn test_static_style_only() {
+        let mut ir = base_convert("<p style='color: red'/>");
+        BaseTransformer::transform(&mut ir, NormalizeProp);
+        let vn = cast!(&ir.body[0], IRNode::VNodeCall);
+        let props = cast!(vn.props.as_ref().unwrap(), Js::Props);
+        assert!(matches!(props[0].1, Js::Props(..)));
+    }
+    #[test]
+    fn test_v_bind_with_normal_prop() {
+        let mut ir = base_convert("<comp v-bind='obj' id='k'/>");
+        BaseTransformer::transform(&mut ir, NormalizeProp);
+        let args = get_merge_args(&ir);
+        assert!(matches!(args[0], Js::Simple(..)));
+    }
+    #[test]
+    fn test_hoist_for_class() {
+        let hoist = |s| {
+            let mut ir = base_convert(s);
+            let passes = crate::chain![NormalizeProp, HoistForClass::default()];
+            BaseTransformer::transform(&mut ir, passes);
+            ir.top_scope.classes.pop()
+        };
+        let cls = hoist("<p v-for='item in list' :class='cls'/>");
+        assert!(matches!(cls, Some(Js::Call(RH::NORMALIZE_CLASS, _))));
+        assert!(hoist("<p v-for='item in list' :class='item'/>").is_none());
+        assert!(hoist("<p v-for='(item, i) in list' :class='[i]'/>").is_none());
+        assert!(hoist("<p v-for='item in list' :class='f()'/>").is_none());
+        assert!(hoist("<p v-for='item in list' :class='{ a: b.c(d) }'/>").is_none());
+        assert!(hoist("<p v-for='item in list' :class='{ a: b[\"c()\"] }'/>").is_some());
+        // not the class of children
+        assert!(hoist("<p v-for='item in list'><b :class='cls'/></p>").is_none());
+        // class may be invalid outside of v-if, v-for or slot
+        assert!(hoist("<p v-if='ok' v-for='item in list' :class='cls'/>").is_none());
+        assert!(hoist("<i v-for='a in b'><p v-for='item in list' :class='cls'/></i>").is_none());
+        assert!(hoist("<comp><p v-for='item in list' :class='cls'/></comp>").is_none());
+    }
+}
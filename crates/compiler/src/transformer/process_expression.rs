@@ -6,7 +6,7 @@ use super::{BaseInfo, CorePassExt, Scope};
 use crate::converter::v_on::get_handler_type;
 use crate::error::{CompilationError, CompilationErrorKind as ErrorKind, RcErrHandle};
 use crate::flags::{RuntimeHelper as RH, StaticLevel};
-use crate::ir::JsExpr as Js;
+use crate::ir::{HandlerType, JsExpr as Js};
 use crate::util::{is_global_allow_listed, is_simple_identifier, rslint, VStr};
 use crate::{cast, BindingTypes, SFCInfo, SourceLocation};
 
@@ -40,6 +40,9 @@ impl<'a, 'b> CorePassExt<BaseInfo<'a>, Scope<'a>> for ExpressionProcessor<'a, 'b
             _ => panic!("only Js::Param is legal"),
         };
     }
+    fn enter_js_expr(&mut self, e: &mut Js<'a>, shared: &mut Scope<'a>) {
+        self.unwrap_display_string(e, shared);
+    }
     // only transform expression after its' sub-expression is transformed
     // e.g. compound/array/call expression
     fn exit_js_expr(&mut self, e: &mut Js<'a>, shared: &mut Scope<'a>) {
@@ -77,6 +80,28 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
             }
         })
     }
+    /// `{{ str }}` of a known string binding is displayed as is.
+    fn unwrap_display_string(&self, e: &mut Js<'a>, scope: &Scope) {
+        if !self.prefix_identifier {
+            return;
+        }
+        let arg = match e {
+            Js::Call(RH::TO_DISPLAY_STRING, args) if args.len() == 1 => &args[0],
+            _ => return,
+        };
+        let name = match arg {
+            Js::Simple(v, _) if is_simple_identifier(*v) => v.raw,
+            _ => return,
+        };
+        let bindings = &self.sfc_info.binding_metadata;
+        if scope.has_identifier(name) || bindings.get(name) != Some(&BindingTypes::LiteralString) {
+            return;
+        }
+        if let Js::Call(_, args) = e {
+            let arg = args.pop().unwrap();
+            *e = arg;
+        }
+    }
     fn process_expression(&self, e: &mut Js<'a>, scope: &mut Scope) {
         if !self.prefix_identifier {
             return;
@@ -88,6 +113,10 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         use crate::ir::HandlerType::InlineStmt;
         // complex expr will be handled recursively in transformer
         let (exp, mut mock_js) = match e {
+            Js::FuncSimple { src, lvl, .. } if VStr::is_event_assign(src) => {
+                *e = self.process_model_assign(*src, *lvl, scope);
+                return;
+            }
             Js::FuncSimple { src, lvl, .. } => (*src, Js::Simple(*src, *lvl)),
             Js::Simple(..) => return self.process_simple_expr(e, scope),
             _ => return,
@@ -115,6 +144,44 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         }
     }
 
+    /// v-model handler writes $event to the model's binding, e.g.
+    /// `$event => (($setup.model) = $event)` or `$event => ((model.value) = $event)`
+    fn process_model_assign(&self, mut src: VStr<'a>, lvl: StaticLevel, scope: &Scope) -> Js<'a> {
+        src.unassign_event();
+        let assign = Js::Src(" = $event");
+        let binding = self.sfc_info.binding_metadata.get(&src.raw);
+        let inline_bind = binding.filter(|_| {
+            self.sfc_info.inline && is_simple_identifier(src) && !scope.has_identifier(src.raw)
+        });
+        let body = match inline_bind {
+            // let binding checks isRef at runtime and assigns itself
+            Some(BindingTypes::SetupLet) => {
+                let ctx = CtxType::Assign(assign);
+                vec![rewrite_inline_identifier(
+                    src,
+                    lvl,
+                    &BindingTypes::SetupLet,
+                    ctx,
+                )]
+            }
+            Some(bind) => {
+                let ctx = CtxType::Assign(assign.clone());
+                let target = rewrite_inline_identifier(src, lvl, bind, ctx);
+                vec![Js::Src("("), target, Js::Src(")"), assign]
+            }
+            None => {
+                let mut target = Js::Simple(src, lvl);
+                self.process_simple_expr(&mut target, scope);
+                vec![Js::Src("("), target, Js::Src(")"), assign]
+            }
+        };
+        Js::FuncCompound {
+            body,
+            ty: HandlerType::InlineStmt,
+            cache: false,
+        }
+    }
+
     fn process_simple_expr(&self, e: &mut Js<'a>, scope: &Scope) {
         if self.process_expr_fast(e, scope) {
             return;
@@ -140,7 +207,9 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
             // NB: this only applies to simple expression. e.g :prop="constBind()"
             let bindings = &self.sfc_info.binding_metadata;
             let lvl = match bindings.get(raw_exp) {
-                Some(BindingTypes::SetupConst) => StaticLevel::CanSkipPatch,
+                Some(BindingTypes::SetupConst) | Some(BindingTypes::LiteralString) => {
+                    StaticLevel::CanSkipPatch
+                }
                 _ => *level,
             };
             *e = self.rewrite_identifier(*v, lvl, CtxType::NoWrite);
@@ -161,7 +230,7 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         };
         let raw = v.raw;
         let broken = self.break_down_complex_expression(raw, scope);
-        let (broken_atoms, local_ref) = if let Some(pair) = broken {
+        let (mut broken_atoms, local_ref) = if let Some(pair) = broken {
             pair
         } else {
             // TODO: add identifier location
@@ -180,9 +249,15 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
             };
             return;
         }
+        if VStr::is_optional_chain(v) {
+            add_member_dot_atoms(raw, &mut broken_atoms);
+        }
         *e = reunite_atoms(raw, broken_atoms, |atom| {
             let prop = atom.property;
             let id_str = VStr::raw(&raw[atom.range]);
+            if id_str.raw == "." {
+                return Js::Src("?.");
+            }
             let rewritten = self.rewrite_identifier(id_str, StaticLevel::NotStatic, prop.ctx_type);
             if prop.is_obj_shorthand {
                 Js::Compound(vec![Js::StrLit(id_str), Js::Src(": "), rewritten])
@@ -323,6 +398,20 @@ where
     Js::Compound(inner)
 }
 
+/// member access dots are rewritten to ?. like identifier atoms,
+/// so dots in string literals or numbers are left intact.
+fn add_member_dot_atoms<'a>(raw: &str, atoms: &mut FreeVarAtoms<'a>) {
+    let dots = rslint::member_dot_offsets(raw).into_iter().map(|i| Atom {
+        range: i..i + 1,
+        property: FreeVarProp {
+            is_obj_shorthand: false,
+            ctx_type: CtxType::NoWrite,
+        },
+    });
+    atoms.extend(dots);
+    atoms.sort_by_key(|r| r.range.start);
+}
+
 fn rewrite_inline_identifier<'a>(
     raw: VStr<'a>,
     level: StaticLevel,
@@ -330,15 +419,17 @@ fn rewrite_inline_identifier<'a>(
     ctx: CtxType<'a>,
 ) -> Js<'a> {
     use BindingTypes as BT;
-    debug_assert!(level == StaticLevel::NotStatic || bind == &BT::SetupConst);
+    debug_assert!(
+        level == StaticLevel::NotStatic || matches!(bind, BT::SetupConst | BT::LiteralString)
+    );
     let expr = move || Js::Simple(raw, level);
     let dot_value = Js::Compound(vec![expr(), Js::Src(".value")]);
     if VStr::is_event_assign(&raw) {
         todo!("handle event assign differently")
     }
     match bind {
-        BT::SetupConst => expr(),
-        BT::SetupRef => dot_value,
+        BT::SetupConst | BT::LiteralString => expr(),
+        BT::SetupRef | BT::SetupModel => dot_value,
         BT::SetupMaybeRef => {
             // const binding that may or may not be ref
             // if it's not a ref, then assignments don't make sense -
@@ -352,7 +443,7 @@ fn rewrite_inline_identifier<'a>(
         }
         BT::SetupLet => rewrite_setup_let(ctx, expr, dot_value),
         BT::Props => Js::Compound(vec![Js::Src("__props."), expr()]),
-        BT::Data | BT::Options => Js::Compound(vec![Js::Src("_ctx."), expr()]),
+        BT::Data | BT::Options => Js::Simple(*raw.clone().prefix_ctx(), level),
     }
 }
 
@@ -450,6 +541,22 @@ mod test {
         assert_eq!(expr.into_string(), "_ctx.a");
     }
     #[test]
+    fn test_literal_prop() {
+        let ir = transform("<p :count='5' :flag='true' :obj='{ a: 1 }'/>");
+        let vn = cast!(first_child(ir), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        let expected = ["5", "true", "{ a: 1 }"];
+        for ((_, val), exp) in props.iter().zip(expected.iter()) {
+            match val {
+                Js::Simple(v, lvl) => {
+                    assert_eq!(v.into_string(), *exp);
+                    assert!(*lvl == StaticLevel::CanStringify);
+                }
+                _ => panic!("literal should not be rewritten"),
+            }
+        }
+    }
+    #[test]
     fn test_v_bind_prefix() {
         let ir = transform("<p v-bind='b'/>");
         let vn = cast!(&ir.body[0], IRNode::VNodeCall);
@@ -546,4 +653,32 @@ mod test {
         let kind = &errs[0].kind;
         assert!(matches!(kind, ErrorKind::InvalidExpression));
     }
+
+    #[test]
+    fn test_literal_string_skips_display() {
+        use crate::BindingMetadata;
+        let map = std::iter::once(("msg", BindingTypes::LiteralString)).collect();
+        let sfc_info = SFCInfo {
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        let transform = |s| {
+            let mut ir = base_convert(s);
+            let exp = ExpressionProcessor {
+                prefix_identifier: true,
+                sfc_info: &sfc_info,
+                err_handle: Rc::new(NoopErrorHandler),
+            };
+            BaseTransformer::transform(&mut ir, transformer_ext(exp));
+            let text = cast!(first_child(ir), IRNode::TextCall);
+            text.texts.into_iter().next().unwrap()
+        };
+        // known string is not wrapped
+        let text = transform("{{msg}}");
+        let v = cast!(text, Js::Compound);
+        assert!(matches!(&v[1], Js::Simple(s, _) if s.raw == "msg"));
+        // unknown binding is still wrapped
+        let text = transform("{{other}}");
+        assert!(matches!(text, Js::Call(RH::TO_DISPLAY_STRING, _)));
+    }
 }
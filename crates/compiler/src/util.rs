@@ -59,6 +59,35 @@ pub fn is_mergeable_prop(prop: &str) -> bool {
     prop == "class" || prop == "style" || is_event_prop(prop)
 }
 
+/// parse static style into declarations, e.g. `color: red` to ("color", "red").
+/// NB: semicolons inside parens like `url(a;b)` do not split.
+pub fn parse_string_style(s: &str) -> Vec<(&str, &str)> {
+    let mut ret = vec![];
+    let mut push = |decl: &str| {
+        if let Some((k, v)) = decl.split_once(':') {
+            let k = k.trim();
+            if !k.is_empty() {
+                ret.push((k, v.trim()));
+            }
+        }
+    };
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    push(&s[start..]);
+    ret
+}
+
 #[inline]
 pub fn not_js_identifier(c: char) -> bool {
     !c.is_alphanumeric() && c != '$' && c != '_'
@@ -108,7 +137,10 @@ const ALLOWED_GLOBALS: &[&str] = make_list![
     Set,
     JSON,
     Intl,
-    BigInt
+    BigInt,
+    console,
+    Error,
+    Symbol
 ];
 pub fn is_global_allow_listed(s: &str) -> bool {
     ALLOWED_GLOBALS.contains(&s)
@@ -142,6 +174,8 @@ pub const fn no(_: &str) -> bool {
     false
 }
 
+/// Plain elements use createElementVNode/createElementBlock while components,
+/// including built-in symbols like Transition, use createVNode/createBlock.
 pub fn get_vnode_call_helper(v: &VNodeIR<BaseConvertInfo>) -> RuntimeHelper {
     use RuntimeHelper as RH;
     if v.is_block {
@@ -472,6 +506,15 @@ mod test {
     use super::*;
     use crate::parser::test::mock_element;
 
+    #[test]
+    fn test_parse_string_style() {
+        let decls = parse_string_style("color: red; background:url(a;b) ;;");
+        assert_eq!(decls, vec![("color", "red"), ("background", "url(a;b)")]);
+        let decls = parse_string_style("font: 12px/1.5 a:b");
+        assert_eq!(decls, vec![("font", "12px/1.5 a:b")]);
+        assert!(parse_string_style("").is_empty());
+    }
+
     #[test]
     fn test_find_dir() {
         let e = mock_element("<p v-if=true/>");
@@ -314,6 +314,35 @@ fn is_member_expr_impl(expr_opt: Option<Expr>) -> bool {
     }
 }
 
+/// Returns offsets of member access dots in text, e.g. `a.b['c.d']` gives [1].
+/// Dots in literals, numbers, spread and existing `?.` are not included.
+pub fn member_dot_offsets(text: &str) -> Vec<usize> {
+    let expr = match parse_js_expr(text) {
+        Some(e) => e,
+        None => return vec![],
+    };
+    let mut dots: Vec<_> = expr
+        .syntax()
+        .descendants()
+        .filter(|n| n.kind() == SyntaxKind::DOT_EXPR)
+        .filter_map(|n| {
+            n.children_with_tokens()
+                .find(|t| t.kind() == SyntaxKind::DOT)
+        })
+        .map(|t| Range::<usize>::from(t.text_range()).start)
+        .collect();
+    dots.sort_unstable();
+    dots
+}
+
+/// Whether the expression calls a function, e.g. `f()`, `a.b()` or `new C()`,
+/// whose result may differ every time it is evaluated.
+pub fn has_call_expr(expr: &Expr) -> bool {
+    expr.syntax()
+        .descendants()
+        .any(|n| matches!(n.kind(), SyntaxKind::CALL_EXPR | SyntaxKind::NEW_EXPR))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -360,6 +389,17 @@ mod test {
         assert!(parse_js_expr("if (a) {b} else {c}").is_none());
         // assert!(parse_js_expr("{a = 4}").is_none()); // TODO
     }
+    #[test]
+    fn test_has_call_expr() {
+        let has_call = |s| has_call_expr(&parse_js_expr(s).unwrap());
+        assert!(has_call("f()"));
+        assert!(has_call("a.b(c)"));
+        assert!(has_call("{ a: new C() }"));
+        assert!(has_call("[a, b ? f() : c]"));
+        assert!(!has_call("a.b"));
+        assert!(!has_call("{ a: b, c: d['(e)'] }"));
+        assert!(!has_call("'f()'"));
+    }
 
     fn walk_ident(s: &str) -> Vec<String> {
         let expr = parse_js_expr(s).unwrap();
@@ -5,7 +5,7 @@
 //! * interning/cache can be optional, e.g. Text Token can skip it at all.
 use super::{
     is_event_prop, non_whitespace, not_js_identifier, json::write_json_string,
-    decode_html::decode_entities,
+    decode_html::decode_entities, rslint,
 };
 use bitflags::bitflags;
 use std::{
@@ -30,6 +30,8 @@ bitflags! {
         const CAMEL_CASE          = 1 << 7;
         const CAPITALIZED         = 1 << 8;
         const JS_STRING           = 1 << 9;
+        // NB: must precede CTX_PREFIX, _ctx. itself is never optional
+        const OPTIONAL_CHAIN      = 1 << 10;
         const CTX_PREFIX          = 1 << 11;
         const MOD_SUFFIX          = 1 << 12;
         const ASSIGN_EVT          = 1 << 13;
@@ -39,7 +41,8 @@ bitflags! {
         /// Ops that can be safely carried out multiple times
         const IDEMPOTENT_OPS =
             Self::COMPRESS_WHITESPACE.bits | Self::DECODE_ENTITY.bits |
-            Self::CAMEL_CASE.bits | Self::CAPITALIZED.bits | Self::DECODE_ATTR.bits;
+            Self::CAMEL_CASE.bits | Self::CAPITALIZED.bits | Self::DECODE_ATTR.bits |
+            Self::OPTIONAL_CHAIN.bits;
         /// Ops that can only be performed at most once. Name comes from
         /// https://en.wikipedia.org/wiki/Substructural_type_system
         const AFFINE_OPS =
@@ -149,6 +152,17 @@ fn write_valid_asset<W: Write>(mut s: &str, mut w: W, asset: &str) -> fmt::Resul
     Ok(())
 }
 
+/// a.b.c -> a?.b?.c, only dots of member access are replaced.
+fn write_optional_chain<W: Write>(s: &str, mut w: W) -> fmt::Result {
+    let mut last = 0;
+    for dot in rslint::member_dot_offsets(s) {
+        w.write_str(&s[last..dot])?;
+        w.write_str("?.")?;
+        last = dot + 1;
+    }
+    w.write_str(&s[last..])
+}
+
 impl StrOps {
     // ideally it should be str.satisfy(op) but adding a trait
     // to str is too much. Use passive voice.
@@ -203,6 +217,7 @@ impl StrOps {
                 w.write_str("onUpdate:")?;
                 w.write_str(s)
             }
+            StrOps::OPTIONAL_CHAIN => write_optional_chain(s, w),
             StrOps::CTX_PREFIX => {
                 w.write_str("_ctx.")?;
                 w.write_str(s)
@@ -280,6 +295,9 @@ impl<'a> VStr<'a> {
     pub fn is_ctx_prefixed(s: &VStr) -> bool {
         s.ops.contains(StrOps::CTX_PREFIX)
     }
+    pub fn is_optional_chain(s: &VStr) -> bool {
+        s.ops.contains(StrOps::OPTIONAL_CHAIN)
+    }
     pub fn is_event_assign(s: &VStr) -> bool {
         s.ops.contains(StrOps::ASSIGN_EVT)
     }
@@ -366,6 +384,11 @@ impl<'a> VStr<'a> {
         self.ops |= StrOps::CTX_PREFIX;
         self
     }
+    /// convert member access to optional chaining: a.b -> a?.b
+    pub fn optional_chain(&mut self) -> &mut Self {
+        self.ops |= StrOps::OPTIONAL_CHAIN;
+        self
+    }
     pub fn suffix_mod(&mut self) -> &mut Self {
         self.ops |= StrOps::MOD_SUFFIX;
         self
@@ -374,6 +397,11 @@ impl<'a> VStr<'a> {
         self.ops |= StrOps::ASSIGN_EVT;
         self
     }
+    /// get the assignment target back from an event assignment
+    pub fn unassign_event(&mut self) -> &mut Self {
+        self.ops.remove(StrOps::ASSIGN_EVT);
+        self
+    }
     pub fn into_string(self) -> String {
         let mut ret = String::new();
         self.write_to(&mut ret).expect("string should never fail");
@@ -483,6 +511,21 @@ mod test {
             ("a^_^", StrOps::VALID_COMP, "_component_a94_94"),
             ("a--b", StrOps::VALID_DIR, "_directive_a__b"),
             ("a--", StrOps::VALID_DIR, "_directive_a__"),
+            ("a.b.c", StrOps::OPTIONAL_CHAIN, "a?.b?.c"),
+            ("a?.b.c", StrOps::OPTIONAL_CHAIN, "a?.b?.c"),
+            ("a[1.5].b", StrOps::OPTIONAL_CHAIN, "a[1.5]?.b"),
+            ("a['x.y']", StrOps::OPTIONAL_CHAIN, "a['x.y']"),
+            (
+                "a.b[`${c.d}`].e",
+                StrOps::OPTIONAL_CHAIN,
+                "a?.b[`${c?.d}`]?.e",
+            ),
+            ("a[f(...b)].c", StrOps::OPTIONAL_CHAIN, "a[f(...b)]?.c"),
+            (
+                "a.b",
+                StrOps::OPTIONAL_CHAIN | StrOps::CTX_PREFIX,
+                "_ctx.a?.b",
+            ),
         ];
         for (src, ops, expect) in cases {
             let origin = ops;
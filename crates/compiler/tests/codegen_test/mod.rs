@@ -1,6 +1,7 @@
 use vue_compiler_core as compiler;
+use compiler::{BindingMetadata, BindingTypes, SFCInfo};
 use super::common::get_compiler;
-use compiler::compiler::TemplateCompiler;
+use compiler::compiler::{BaseCompiler, CompileOption, TemplateCompiler, get_base_passes};
 use crate::meta_macro;
 use rslint_parser::parse_text;
 
@@ -30,3 +31,1226 @@ fn test_text_codegen() {
         "<comp>Hello {{world}}</comp>",
     ]];
 }
+
+#[test]
+fn test_v_if_codegen() {
+    assert_codegen![["<p v-if='a'/>", "<p v-if='a'/><span v-else/>"]];
+}
+
+#[test]
+fn test_v_for_codegen() {
+    assert_codegen![["<p v-for='a in b'/>"]];
+}
+
+#[test]
+fn test_slot_codegen() {
+    assert_codegen![["<slot/>", "<slot name='foo'/>"]];
+}
+
+#[test]
+fn test_component_codegen() {
+    assert_codegen![["<comp/>", "<comp :a='b'/>", "<comp><p/></comp>"]];
+}
+
+#[test]
+fn test_element_codegen() {
+    assert_codegen![["<p class='a'/>", "<p :id='b'/>", "<p><span/><span/></p>"]];
+}
+
+fn compile_with_option(source: &str, option: CompileOption) -> String {
+    let sfc_info = Default::default();
+    let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+    let ret = compiler.compile(source, &sfc_info).unwrap();
+    String::from_utf8(ret).unwrap()
+}
+
+#[test]
+fn test_reserve_writer() {
+    fn reserve(w: &mut Vec<u8>, size: usize) {
+        assert!(size > 0);
+        w.reserve(size);
+    }
+    let src = "<div :id='a'><p v-for='i in list'>{{ i }}</p></div>";
+    let sfc_info = Default::default();
+    let compiler = BaseCompiler::new(Vec::new, get_base_passes, Default::default());
+    let ret = compiler
+        .with_reserve(reserve)
+        .compile(src, &sfc_info)
+        .unwrap();
+    let s = String::from_utf8(ret).unwrap();
+    assert_eq!(s, compile_with_option(src, Default::default()));
+}
+
+#[test]
+fn test_custom_element() {
+    let source = "<my-widget :a='b'/>";
+    let option = CompileOption {
+        is_native_tag: |t| t != "my-widget",
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    assert!(s.contains(r#"_resolveComponent("my-widget")"#), "{}", s);
+    let option = CompileOption {
+        is_native_tag: |t| t != "my-widget",
+        is_custom_element: |t| t == "my-widget",
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    assert!(s.contains(r#"("my-widget", "#), "{}", s);
+    assert!(!s.contains("resolveComponent"), "{}", s);
+}
+
+#[test]
+fn test_text_only_element() {
+    let s = base_compile("<span>{{msg}}</span>");
+    let expected = r#"_createElementVNode("span", null, _toDisplayString(msg), 1 /*TEXT*/)"#;
+    assert!(s.contains(expected), "{}", s);
+    assert!(!s.contains("_createTextVNode"), "{}", s);
+    // component children are not optimized
+    let s = base_compile("<comp>{{msg}}</comp>");
+    assert!(!s.contains(", _toDisplayString(msg), 1"), "{}", s);
+}
+
+#[test]
+fn test_fmt_write_codegen() {
+    use compiler::codegen::{CodeGenInfo, CodeGenerator, FmtCodeGen};
+    let source = "<p :class='a'>Hello {{world}}</p>";
+    let sfc_info = Default::default();
+    let compiler = get_compiler();
+    let tokens = compiler.scan(source);
+    let ast = compiler.parse(tokens);
+    let mut ir = compiler.convert(ast, &sfc_info);
+    compiler.transform(&mut ir, &sfc_info);
+    let mut out = String::new();
+    let info = CodeGenInfo {
+        writer: &mut out,
+        sfc_info: &sfc_info,
+    };
+    let generator = FmtCodeGen::new(CompileOption::default().codegen());
+    let result = generator.generate(ir, info).unwrap();
+    assert!(result.warnings.is_empty());
+    assert_eq!(out, base_compile(source));
+}
+
+#[test]
+fn test_style_merge() {
+    let s = base_compile("<p style='color: red' :style=\"{ fontSize: x + 'px' }\"/>");
+    assert!(s.contains("style: _normalizeStyle([{"), "{}", s);
+    assert!(s.contains("color: \"red\""), "{}", s);
+    assert!(s.contains("{ fontSize: x + 'px' }])"), "{}", s);
+}
+
+#[test]
+fn test_class_merge() {
+    let s = base_compile("<p class='a' :class='b'/>");
+    assert!(s.contains(r#"class: _normalizeClass(["a", b])"#), "{}", s);
+    let s = base_compile("<p :class='b' class='a'/>");
+    assert!(s.contains(r#"class: _normalizeClass([b, "a"])"#), "{}", s);
+}
+
+#[test]
+fn test_attrs_passthrough() {
+    use compiler::codegen::ScriptMode;
+    let prefix_option = || CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option("<div v-bind='$attrs'/>", prefix_option());
+    let expected = "_normalizeProps(_guardReactiveProps(_ctx.$attrs))";
+    assert!(s.contains(expected), "{}", s);
+    let s = compile_with_option("<div v-bind='$attrs' class='a'/>", prefix_option());
+    assert!(s.contains("_mergeProps(_ctx.$attrs, {"), "{}", s);
+    assert!(s.contains(r#"class: "a""#), "{}", s);
+}
+
+#[test]
+fn test_keyed_template_v_for() {
+    let s = base_compile("<template v-for='i in list' :key='i'><p>{{i}}</p><span/></template>");
+    assert!(s.contains("_renderList(list, (i) =>"), "{}", s);
+    assert!(s.contains("_createElementBlock(_Fragment, {\n"), "{}", s);
+    assert_eq!(s.matches("key: i").count(), 1, "{}", s);
+    assert!(s.contains(r#"_createElementVNode("p", null"#), "{}", s);
+    assert!(s.contains(r#"_createElementVNode("span")"#), "{}", s);
+}
+
+#[test]
+fn test_static_helper_import() {
+    use compiler::codegen::ScriptMode;
+    let module_option = || CompileOption {
+        mode: ScriptMode::Module {
+            runtime_module_name: "vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option("Hello world", module_option());
+    assert!(!s.contains("import"), "{}", s);
+    let s = compile_with_option("<p>Hello</p>", module_option());
+    assert!(
+        s.contains("createElementVNode as _createElementVNode"),
+        "{}",
+        s
+    );
+    assert!(!s.contains("toDisplayString"), "{}", s);
+    assert_eq!(s.matches(" as _").count(), 1, "{}", s);
+}
+
+#[test]
+fn test_text_patch_flag() {
+    let s = base_compile("<span>{{x}}</span>");
+    assert!(s.contains("_toDisplayString(x), 1 /*TEXT*/)"), "{}", s);
+    let s = base_compile("<span>static</span>");
+    assert!(
+        s.contains(r#"_createElementVNode("span", null, "static")"#),
+        "{}",
+        s
+    );
+    let s = base_compile("<span>a {{x}} b</span>");
+    assert!(
+        s.contains(r#""a " + _toDisplayString(x) + " b", 1 /*TEXT*/)"#),
+        "{}",
+        s
+    );
+    let option = CompileOption {
+        is_dev: false,
+        ..Default::default()
+    };
+    let s = compile_with_option("<span>{{x}}</span>", option);
+    assert!(s.contains("_toDisplayString(x), 1)"), "{}", s);
+}
+
+#[test]
+fn test_prop_patch_flag() {
+    let s = base_compile("<p :class='a'/>");
+    assert!(s.contains(", null, 2 /*CLASS*/)"), "{}", s);
+    let s = base_compile("<p :style='a'/>");
+    assert!(s.contains(", null, 4 /*STYLE*/)"), "{}", s);
+    let s = base_compile("<p :id='a'/>");
+    assert!(s.contains(r#", null, 8 /*PROPS*/, ["id"])"#), "{}", s);
+    let s = base_compile("<p :class='a' :id='b'/>");
+    assert!(
+        s.contains(r#", null, 10 /*CLASS | PROPS*/, ["id"])"#),
+        "{}",
+        s
+    );
+    // static attributes need no patch
+    let s = base_compile("<p class='a' id='b'/>");
+    assert!(!s.contains("/*"), "{}", s);
+}
+
+#[test]
+fn test_full_props_flag() {
+    let s = base_compile("<div v-bind='obj'/>");
+    assert!(s.contains(", null, 16 /*FULL_PROPS*/)"), "{}", s);
+    // spread overrides finer grained flags and dynamic prop names
+    let s = base_compile("<div :id='a' :class='b' v-bind='obj'/>");
+    assert!(s.contains(", null, 16 /*FULL_PROPS*/)"), "{}", s);
+    assert!(!s.contains(r#"["id"]"#), "{}", s);
+}
+
+#[test]
+fn test_dynamic_props_order() {
+    let s = base_compile("<div :a='x' :b='y'/>");
+    assert!(s.contains(r#"8 /*PROPS*/, ["a", "b"])"#), "{}", s);
+    // names are sorted regardless of source order
+    let s = base_compile("<div :c='x' :a='y' :b='z'/>");
+    assert!(s.contains(r#"8 /*PROPS*/, ["a", "b", "c"])"#), "{}", s);
+}
+
+#[test]
+fn test_need_patch_flag() {
+    let s = base_compile("<div ref='x'/>");
+    assert!(s.contains("}, null, 512 /*NEED_PATCH*/)"), "{}", s);
+    let s = base_compile("<div v-foo/>");
+    assert!(
+        s.contains(r#"("div", null, null, 512 /*NEED_PATCH*/)"#),
+        "{}",
+        s
+    );
+    // other dynamic bindings already patch the element
+    let s = base_compile("<div ref='x' :id='a'/>");
+    assert!(s.contains(r#"8 /*PROPS*/, ["id"])"#), "{}", s);
+    assert!(!s.contains("NEED_PATCH"), "{}", s);
+}
+
+#[test]
+fn test_v_for_child_key() {
+    use compiler::codegen::ScriptMode;
+    let source = "<li v-for='item in items' :key='item.id'>{{item.name}}</li>";
+    let s = base_compile(source);
+    assert!(s.contains("_renderList(items, (item) => "), "{}", s);
+    assert!(s.contains("key: item.id,"), "{}", s);
+    assert!(s.contains("128 /*KEYED_FRAGMENT*/"), "{}", s);
+    // loop alias is not prefixed
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    assert!(s.contains("_renderList(_ctx.items, (item) => "), "{}", s);
+    assert!(s.contains("key: item.id,"), "{}", s);
+}
+
+#[test]
+fn test_check_helpers() {
+    let option = CompileOption {
+        check_helpers: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("<p v-for='a in b'>{{a}}</p>", option);
+    assert!(s.contains("_renderList(b"), "{}", s);
+}
+
+#[test]
+fn test_missing_helper_error() {
+    use compiler::converter::{BaseConvertInfo, BaseRoot};
+    use compiler::flags::RuntimeHelper;
+    use compiler::transformer::CorePass;
+    use compiler::{Chain, SFCInfo};
+    struct ForgetHelper;
+    impl<'a> CorePass<BaseConvertInfo<'a>> for ForgetHelper {
+        fn exit_root(&mut self, r: &mut BaseRoot<'a>) {
+            r.top_scope.helpers.remove(RuntimeHelper::TO_DISPLAY_STRING);
+        }
+    }
+    // base passes exit root first, so the helper is removed after collection
+    fn get_passes<'a>(
+        sfc_info: &'a SFCInfo<'a>,
+        opt: &CompileOption,
+    ) -> impl CorePass<BaseConvertInfo<'a>> {
+        Chain {
+            first: ForgetHelper,
+            second: get_base_passes(sfc_info, opt),
+        }
+    }
+    let option = CompileOption {
+        check_helpers: true,
+        ..Default::default()
+    };
+    let sfc_info = Default::default();
+    let compiler = BaseCompiler::new(Vec::new, get_passes, option);
+    let err = compiler.compile("<p>{{a}}</p>", &sfc_info).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_compact_mode() {
+    let option = || CompileOption {
+        compact: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("Hello world", option());
+    let expected = r#"return function render(_ctx, _cache) {with (_ctx) {return "Hello world"}}"#;
+    assert_eq!(s, expected);
+    let s = compile_with_option("<p v-if='a'>{{b}}</p>", option());
+    assert!(!s.contains('\n'), "{}", s);
+    assert!(s.contains("} = _Vue;return "), "{}", s);
+    let wrapped = format!("function () {{ {} }}", s);
+    assert!(parse_text(&wrapped, 0).errors().is_empty(), "{}", s);
+}
+
+#[test]
+fn test_v_for_arity() {
+    let s = base_compile("<p v-for='(v, k) in obj'/>");
+    assert!(s.contains("_renderList(obj, (v, k) => "), "{}", s);
+    let s = base_compile("<p v-for='(v, k, i) in obj'/>");
+    assert!(s.contains("_renderList(obj, (v, k, i) => "), "{}", s);
+    // skipped key gets a placeholder param
+    let s = base_compile("<p v-for='(v, , i) in obj'/>");
+    assert!(s.contains("_renderList(obj, (v, _1, i) => "), "{}", s);
+    let s = base_compile("<p v-for='n in 5'/>");
+    assert!(s.contains("_renderList(5, (n) => "), "{}", s);
+    // number range source is stable
+    assert!(s.contains("64 /*STABLE_FRAGMENT*/"), "{}", s);
+}
+
+#[test]
+fn test_v_pre_passthrough() {
+    let s = base_compile("<div v-pre>{{ notInterpolated }}</div>");
+    assert!(s.contains(r#""{{ notInterpolated }}""#), "{}", s);
+    assert!(!s.contains("toDisplayString"), "{}", s);
+    let s = base_compile("<div v-pre><p :a='b' @click='c'>{{d}}</p></div>");
+    assert!(s.contains(r#"":a": "b""#), "{}", s);
+    assert!(!s.contains("toDisplayString"), "{}", s);
+}
+
+#[test]
+fn test_define_model() {
+    use compiler::codegen::ScriptMode;
+    let compile = |inline| {
+        let map = std::iter::once(("model", BindingTypes::SetupModel)).collect();
+        let sfc_info = SFCInfo {
+            inline,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        let option = CompileOption {
+            mode: ScriptMode::Function {
+                prefix_identifier: true,
+                runtime_global_name: "Vue".into(),
+            },
+            ..Default::default()
+        };
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let ret = compiler
+            .compile("<comp v-model='model'/>", &sfc_info)
+            .unwrap();
+        String::from_utf8(ret).unwrap()
+    };
+    let s = compile(false);
+    assert!(s.contains("modelValue: $setup.model"), "{}", s);
+    let handler = r#""onUpdate:modelValue": $event => (($setup.model) = $event)"#;
+    assert!(s.contains(handler), "{}", s);
+    let s = compile(true);
+    assert!(s.contains("modelValue: model.value"), "{}", s);
+    let handler = r#""onUpdate:modelValue": $event => ((model.value) = $event)"#;
+    assert!(s.contains(handler), "{}", s);
+}
+
+#[test]
+fn test_slot_with_ctx_flag() {
+    let s =
+        base_compile("<comp><template #header>a</template><template #default>b</template></comp>");
+    assert!(s.contains("header: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("default: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("_: 1 /*Stable*/"), "{}", s);
+    assert!(s.contains("withCtx: _withCtx"), "{}", s);
+    // slot content forwarding parent slot
+    let s = base_compile("<comp><template #header><slot/></template></comp>");
+    assert!(s.contains("header: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("_: 3 /*Forwarded*/"), "{}", s);
+    // conditional slot is dynamic
+    let s = base_compile("<comp><template v-if='a' #header>b</template></comp>");
+    assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+}
+
+#[test]
+fn test_whitespace_strategy() {
+    use compiler::parser::WhitespaceStrategy;
+    let option = |whitespace| CompileOption {
+        whitespace,
+        ..Default::default()
+    };
+    // leading/trailing whitespaces are dropped in both modes
+    for ws in [WhitespaceStrategy::Condense, WhitespaceStrategy::Preserve] {
+        let s = compile_with_option("<div> <span/> </div>", option(ws));
+        assert!(!s.contains("createTextVNode"), "{}", s);
+        assert!(!s.contains(r#""""#), "{}", s);
+    }
+    let case = "<div>\n  <span/>\n  <span/>\n</div>";
+    let s = compile_with_option(case, option(WhitespaceStrategy::Condense));
+    assert!(!s.contains("createTextVNode"), "{}", s);
+    // whitespaces between elements are kept as a single space
+    let s = compile_with_option(case, option(WhitespaceStrategy::Preserve));
+    assert!(s.contains(r#"_createTextVNode(" ")"#), "{}", s);
+}
+
+#[test]
+fn test_teleport() {
+    let s = base_compile(r##"<Teleport to="#app" :disabled="d"><div/></Teleport>"##);
+    assert!(
+        s.contains("(_openBlock(), _createBlock(_Teleport, {"),
+        "{}",
+        s
+    );
+    assert!(s.contains(r##"to: "#app","##), "{}", s);
+    assert!(s.contains("disabled: d"), "{}", s);
+    assert!(s.contains("8 /*PROPS*/, [\"disabled\"]"), "{}", s);
+    // children are rendered as is instead of slot functions
+    assert!(s.contains("_createElementVNode(\"div\")"), "{}", s);
+    assert!(!s.contains("withCtx"), "{}", s);
+}
+
+#[test]
+fn test_keep_alive() {
+    let s = base_compile(
+        r#"<KeepAlive :include="/Foo/" :max="10"><component :is="view"/></KeepAlive>"#,
+    );
+    assert!(
+        s.contains("(_openBlock(), _createBlock(_KeepAlive, {"),
+        "{}",
+        s
+    );
+    assert!(s.contains("include: /Foo/,"), "{}", s);
+    assert!(s.contains("max: 10"), "{}", s);
+    // the only child is a block of dynamic component
+    let child = "(_openBlock(), _createBlock(_resolveDynamicComponent(view)))";
+    assert!(s.contains(child), "{}", s);
+    assert!(s.contains("DYNAMIC_SLOTS"), "{}", s);
+    assert!(!s.contains("withCtx"), "{}", s);
+}
+
+#[test]
+fn test_suspense_slots() {
+    let s = base_compile(
+        "<Suspense><template #default><Async/></template><template #fallback>loading</template></Suspense>",
+    );
+    assert!(
+        s.contains("(_openBlock(), _createBlock(_Suspense, null, {"),
+        "{}",
+        s
+    );
+    assert!(s.contains("default: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("_createVNode(_component_Async)"), "{}", s);
+    assert!(s.contains("fallback: _withCtx(() => ["), "{}", s);
+    assert!(s.contains(r#"_createTextVNode("loading")"#), "{}", s);
+    assert!(s.contains("_: 1 /*Stable*/"), "{}", s);
+}
+
+#[test]
+fn test_line_comments() {
+    let case = "<div>\n  <p>a</p>\n  <span/>\n</div>";
+    let option = |is_dev| CompileOption {
+        line_comments: true,
+        is_dev,
+        ..Default::default()
+    };
+    let s = compile_with_option(case, option(true));
+    assert!(
+        s.contains("/* line 1 */ (_openBlock(), _createElementBlock(\"div\""),
+        "{}",
+        s
+    );
+    assert!(
+        s.contains("/* line 2 */ _createElementVNode(\"p\""),
+        "{}",
+        s
+    );
+    assert!(
+        s.contains("/* line 3 */ _createElementVNode(\"span\")"),
+        "{}",
+        s
+    );
+    // off in production and by default
+    let s = compile_with_option(case, option(false));
+    assert!(!s.contains("/* line"), "{}", s);
+    let s = base_compile(case);
+    assert!(!s.contains("/* line"), "{}", s);
+}
+
+#[test]
+fn test_global_allow_list() {
+    use compiler::codegen::ScriptMode;
+    let prefix_option = || CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option("{{ Math.max(a, b) }}", prefix_option());
+    assert!(s.contains("Math.max(_ctx.a, _ctx.b)"), "{}", s);
+    assert!(!s.contains("_ctx.Math"), "{}", s);
+    let s = compile_with_option("<p @click='console.log(Date.now(), c)'/>", prefix_option());
+    assert!(s.contains("console.log(Date.now(), _ctx.c)"), "{}", s);
+    // bare global identifier takes the fast path
+    let s = compile_with_option("{{JSON}}", prefix_option());
+    assert!(s.contains("_toDisplayString(JSON)"), "{}", s);
+}
+
+#[test]
+fn test_prefix_skips_literals() {
+    use compiler::codegen::ScriptMode;
+    let compile = |s| {
+        let option = CompileOption {
+            mode: ScriptMode::Function {
+                prefix_identifier: true,
+                runtime_global_name: "Vue".into(),
+            },
+            ..Default::default()
+        };
+        compile_with_option(s, option)
+    };
+    let s = compile("{{ a + 'b' + 1 }}");
+    assert!(s.contains("_ctx.a + 'b' + 1"), "{}", s);
+    // member property name is not a free variable
+    let s = compile("{{ a.b + c[d] }}");
+    assert!(s.contains("_ctx.a.b + _ctx.c[_ctx.d]"), "{}", s);
+    // object key is not prefixed but its value is
+    let s = compile("{{ { a: b, 'c': 2 } }}");
+    assert!(s.contains("{ a: _ctx.b, 'c': 2 }"), "{}", s);
+    // only interpolated expressions in template literal are prefixed
+    let s = compile("{{ `a${b}c` }}");
+    assert!(s.contains("`a${_ctx.b}c`"), "{}", s);
+    assert!(!s.contains("_ctx.a"), "{}", s);
+}
+
+#[test]
+fn test_await_in_bound_value() {
+    let s = base_compile("<comp><template #default><p :a='await foo'/></template></comp>");
+    // slot function stays sync and bound value is kept verbatim
+    assert!(s.contains("default: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("a: await foo"), "{}", s);
+    assert_eq!(s.matches("await").count(), 1, "{}", s);
+    assert!(!s.contains("async"), "{}", s);
+}
+
+#[test]
+fn test_inline_arrow() {
+    use compiler::codegen::CodegenTarget;
+    let option = CompileOption {
+        target: CodegenTarget::Functional,
+        ..Default::default()
+    };
+    let s = compile_with_option("<p>{{a}}</p>", option);
+    assert!(!s.contains("function render"), "{}", s);
+    assert!(s.contains("return (_ctx, _cache) => {"), "{}", s);
+    assert!(s.ends_with("}\n}"), "{}", s);
+    let wrapped = format!("function () {{ {} }}", s);
+    assert!(parse_text(&wrapped, 0).errors().is_empty(), "{}", s);
+}
+
+#[test]
+fn test_client_text_not_html_escaped() {
+    // runtime sets textContent so static text stays a plain js string
+    let s = base_compile("<p>a < b & c</p>");
+    assert!(s.contains(r#""a " + "<" + " b & c""#), "{}", s);
+    assert!(!s.contains("&lt;") && !s.contains("&amp;"), "{}", s);
+    let s = base_compile("<p>&lt;&amp;</p>");
+    assert!(s.contains(r#""<&""#), "{}", s);
+}
+
+#[test]
+fn test_v_bind_same_name_shorthand() {
+    use compiler::codegen::ScriptMode;
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option("<div :id/>", option);
+    assert!(s.contains("id: _ctx.id,"), "{}", s);
+    assert!(s.contains(r#"8 /*PROPS*/, ["id"]"#), "{}", s);
+    let s = base_compile("<div :id/>");
+    assert!(s.contains("id: id,"), "{}", s);
+}
+
+#[test]
+fn test_null_props_placeholder() {
+    let s = base_compile("<div><span/></div>");
+    assert!(s.contains(r#"_createElementBlock("div", null, ["#), "{}", s);
+    assert!(s.contains(r#"_createElementVNode("span")"#), "{}", s);
+    // nothing trails after the last non-empty argument
+    assert!(!s.contains(r#"("span", null"#), "{}", s);
+}
+
+#[test]
+fn test_big_number_literal() {
+    use compiler::codegen::ScriptMode;
+    let prefix_option = || CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    // numbers are kept as source text, never reformatted
+    let s = base_compile(r#"<comp :big="9007199254740993"/>"#);
+    assert!(s.contains("big: 9007199254740993,"), "{}", s);
+    let s = compile_with_option(r#"<comp :big="9007199254740993"/>"#, prefix_option());
+    assert!(s.contains("big: 9007199254740993,"), "{}", s);
+    let s = compile_with_option(r#"<comp :big="9007199254740993n"/>"#, prefix_option());
+    assert!(s.contains("big: 9007199254740993n,"), "{}", s);
+    let s = compile_with_option(r#"<comp :big="1e21 + 0x1F"/>"#, prefix_option());
+    assert!(s.contains("big: 1e21 + 0x1F,"), "{}", s);
+}
+
+#[test]
+fn test_static_and_function_ref() {
+    use compiler::codegen::ScriptMode;
+    let s = base_compile(r#"<div ref="x"/>"#);
+    assert!(s.contains(r#"ref: "x","#), "{}", s);
+    let s = base_compile(r#"<div :ref="setRef"/>"#);
+    assert!(s.contains("ref: setRef,"), "{}", s);
+    assert!(!s.contains(r#""setRef""#), "{}", s);
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(r#"<div :ref="el => refs.push(el)"/>"#, option);
+    assert!(s.contains("ref: el => _ctx.refs.push(el),"), "{}", s);
+    assert!(s.contains("512 /*NEED_PATCH*/"), "{}", s);
+}
+
+#[test]
+fn test_compile_cache() {
+    use compiler::compiler::CompileCache;
+    use compiler::error::VecErrorHandler;
+    use std::rc::Rc;
+    // every pipeline run reports the abrupt comment once
+    let src = "<!--><p>{{a}}</p>";
+    let errors = Rc::new(VecErrorHandler::new());
+    let runs = || errors.errors().len();
+    let sfc_info = SFCInfo::default();
+    let scoped = SFCInfo {
+        scope_id: Some("data-v-1".into()),
+        ..Default::default()
+    };
+    let option = CompileOption {
+        error_handler: errors.clone(),
+        ..Default::default()
+    };
+    let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+    let mut cache = CompileCache::default();
+    let first = cache.compile(&compiler, src, &sfc_info).unwrap().to_vec();
+    assert_eq!(runs(), 1);
+    let second = cache.compile(&compiler, src, &sfc_info).unwrap().to_vec();
+    assert_eq!(first, second);
+    assert_eq!(runs(), 1);
+    // different source, SFCInfo or option misses the cache
+    cache
+        .compile(&compiler, "<!--><p>{{b}}</p>", &sfc_info)
+        .unwrap();
+    assert_eq!(runs(), 2);
+    cache.compile(&compiler, src, &scoped).unwrap();
+    assert_eq!(runs(), 3);
+    let option = CompileOption {
+        is_dev: false,
+        error_handler: errors.clone(),
+        ..Default::default()
+    };
+    let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+    cache.compile(&compiler, src, &sfc_info).unwrap();
+    assert_eq!(runs(), 4);
+    assert_eq!(cache.len(), 4);
+}
+
+#[test]
+fn test_v_for_arrow_body() {
+    // single vnode is the expression body
+    let s = base_compile("<p v-for='a in b'>{{a}}</p>");
+    assert!(
+        s.contains("_renderList(b, (a) => _createElementVNode(\"p\""),
+        "{}",
+        s
+    );
+    assert!(!s.contains("(a) => {"), "{}", s);
+    let s = base_compile("<template v-for='a in b'><p/><span/></template>");
+    assert!(s.contains("_renderList(b, (a) => (_openBlock(), "), "{}", s);
+    // memo check writes cache in block body
+    let s = base_compile("<p v-for='a in b' :key='a' v-memo='[a]'/>");
+    assert!(
+        s.contains("_renderList(b, (a, _1, _2, _cached) => {"),
+        "{}",
+        s
+    );
+    assert!(s.contains("return _cached"), "{}", s);
+    assert!(s.contains("_item.memo = _memo"), "{}", s);
+    assert!(s.contains("}, _cache, 0)"), "{}", s);
+}
+
+#[test]
+fn test_v_for_slot_arrow_body() {
+    let s = base_compile("<comp><template v-for='n in list' #[n]>z</template></comp>");
+    // object literal needs block body
+    assert!(s.contains("_renderList(list, (n) => {"), "{}", s);
+    assert!(s.contains("return {"), "{}", s);
+    let wrapped = format!("function () {{ {} }}", s);
+    assert!(parse_text(&wrapped, 0).errors().is_empty(), "{}", s);
+}
+
+#[test]
+fn test_root_template_unwrap() {
+    let s = base_compile("<template><div/></template>");
+    assert!(
+        s.contains(r#"return (_openBlock(), _createElementBlock("div"))"#),
+        "{}",
+        s
+    );
+    assert!(!s.contains("template"), "{}", s);
+    assert!(!s.contains("_Fragment"), "{}", s);
+    // structural template stays a fragment
+    let s = base_compile("<template v-if='a'><div/></template>");
+    assert!(s.contains("_Fragment"), "{}", s);
+    // template with several children is not a wrapper
+    let s = base_compile("<template><div/><p/></template>");
+    assert!(s.contains(r#"_createElementBlock("template""#), "{}", s);
+}
+
+#[test]
+fn test_adjacent_interpolations() {
+    let s = base_compile("<p>{{a}}{{b}}</p>");
+    let expected = r#"("p", null, _toDisplayString(a) + _toDisplayString(b), 1 /*TEXT*/)"#;
+    assert!(s.contains(expected), "{}", s);
+    let s = base_compile("<p>{{a}}{{b}}<span/></p>");
+    let expected = "_createTextVNode(_toDisplayString(a) + _toDisplayString(b), 1 /*TEXT*/)";
+    assert!(s.contains(expected), "{}", s);
+    assert_eq!(s.matches("_createTextVNode(").count(), 1, "{}", s);
+}
+
+#[test]
+fn test_hoist_static() {
+    use compiler::converter::BaseConvertInfo;
+    use compiler::transformer::{hoist_static::HoistStatic, pass::SharedInfoPasses};
+    use compiler::transformer::{CorePass, Scope};
+    use compiler::Chain;
+    use std::marker::PhantomData;
+    // HoistStatic is registered in dom passes, not in base passes
+    fn get_passes<'a>(
+        sfc_info: &'a SFCInfo<'a>,
+        opt: &CompileOption,
+    ) -> impl CorePass<BaseConvertInfo<'a>> {
+        let hoist = SharedInfoPasses {
+            passes: HoistStatic::new(opt.cache_handlers, opt.hoist_static),
+            shared_info: Scope::default(),
+            pd: PhantomData,
+        };
+        Chain {
+            first: get_base_passes(sfc_info, opt),
+            second: hoist,
+        }
+    }
+    let compile = |hoist_static| {
+        let option = CompileOption {
+            hoist_static,
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_passes, option);
+        let ret = compiler.compile(SRC, &sfc_info).unwrap();
+        String::from_utf8(ret).unwrap()
+    };
+    const SRC: &str = r#"<div><p class="a"><span>static</span></p>{{b}}</div>"#;
+    // static subtree is created inline in render
+    let s = compile(false);
+    assert!(!s.contains("_hoisted_"), "{}", s);
+    assert!(s.contains(r#"_createElementVNode("p", {"#), "{}", s);
+    assert!(
+        s.contains(r#"_createElementVNode("span", null, "static")"#),
+        "{}",
+        s
+    );
+    let wrapped = format!("function () {{ {} }}", s);
+    assert!(parse_text(&wrapped, 0).errors().is_empty(), "{}", s);
+    // static subtree is created once outside render
+    let s = compile(true);
+    let hoist = s.find(r#"const _hoisted_0 = _createElementVNode("p", {"#);
+    let render = s.find("return function render");
+    assert!(hoist.is_some() && hoist < render, "{}", s);
+    assert!(s.contains("], -1 /*HOISTED*/)"), "{}", s);
+    assert_eq!(
+        s.matches(r#"_createElementVNode("span""#).count(),
+        1,
+        "{}",
+        s
+    );
+    // dynamic text stays in render and references the hoisted node
+    assert!(s.contains("_hoisted_0, "), "{}", s);
+    assert!(s.contains("_toDisplayString(b)"), "{}", s);
+    assert!(!s.contains("_hoisted_1"), "{}", s);
+    let wrapped = format!("function () {{ {} }}", s);
+    assert!(parse_text(&wrapped, 0).errors().is_empty(), "{}", s);
+}
+
+#[test]
+fn test_create_slots_dynamic_flag() {
+    let s = base_compile(
+        "<comp><template #a>x</template><template v-if='ok' #b>y</template><template v-for='n in list' #[n]>z</template></comp>",
+    );
+    assert!(s.contains("_createSlots({"), "{}", s);
+    assert!(s.contains("a: _withCtx(() => ["), "{}", s);
+    assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+    assert_eq!(s.matches("_: ").count(), 1, "{}", s);
+    // dynamic entries are array elements, missing v-if branch is undefined
+    assert!(s.contains("}, ["), "{}", s);
+    assert!(s.contains("(ok)"), "{}", s);
+    assert!(s.contains(r#"name: "b","#), "{}", s);
+    assert!(s.contains(": undefined"), "{}", s);
+    assert!(s.contains("_renderList(list, (n) => {"), "{}", s);
+    assert!(s.contains("name: n,"), "{}", s);
+}
+
+#[test]
+fn test_v_for_with_v_if() {
+    let s = base_compile(r#"<div v-for="i in list" v-if="i.ok"/>"#);
+    // the condition is checked per item inside renderList
+    assert!(s.contains("_renderList(list, (i) => (i.ok)"), "{}", s);
+    let vnode = s.find(r#"? (_openBlock(), _createElementBlock("div", {"#);
+    assert!(s.contains("key: 0,"), "{}", s);
+    let comment = s.find(r#": _createCommentVNode("v-if", true)"#);
+    assert!(vnode.expect(&s) < comment.expect(&s), "{}", s);
+    // injected v-if key does not make the fragment keyed
+    assert!(s.contains("256 /*UNKEYED_FRAGMENT*/"), "{}", s);
+}
+
+#[test]
+fn test_v_for_index_key() {
+    use compiler::codegen::ScriptMode;
+    let source = "<li v-for='(item, index) in list' :key='index'>{{item}}</li>";
+    let s = base_compile(source);
+    assert!(s.contains("_renderList(list, (item, index) => "), "{}", s);
+    assert!(s.contains("key: index,"), "{}", s);
+    assert!(s.contains("128 /*KEYED_FRAGMENT*/"), "{}", s);
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    assert!(
+        s.contains("_renderList(_ctx.list, (item, index) => "),
+        "{}",
+        s
+    );
+    assert!(s.contains("key: index,"), "{}", s);
+    assert!(s.contains("_toDisplayString(item)"), "{}", s);
+    assert!(
+        !s.contains("_ctx.index") && !s.contains("_ctx.item"),
+        "{}",
+        s
+    );
+}
+
+#[test]
+fn test_iife_global_var() {
+    let option = CompileOption {
+        iife: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("<p>{{a}}</p>", option);
+    assert!(s.starts_with("(function () {\nconst _Vue = Vue"), "{}", s);
+    assert!(
+        s.contains("return function render(_ctx, _cache) {"),
+        "{}",
+        s
+    );
+    assert!(s.ends_with("}\n})()"), "{}", s);
+    assert!(parse_text(&s, 0).errors().is_empty(), "{}", s);
+    let option = CompileOption {
+        global_var: Some("window.render".into()),
+        compact: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("<p>{{a}}</p>", option);
+    assert!(
+        s.starts_with("window.render = (function () {const _Vue = Vue;"),
+        "{}",
+        s
+    );
+    assert!(s.ends_with("}})()"), "{}", s);
+    assert!(!s.contains('\n'), "{}", s);
+    assert!(parse_text(&s, 0).errors().is_empty(), "{}", s);
+}
+
+#[test]
+fn test_component_default_slot_is_function() {
+    let s = base_compile("<comp><span>{{a}}</span></comp>");
+    assert!(s.contains("_createBlock(_component_comp, null, {"), "{}", s);
+    assert!(s.contains("default: _withCtx(() => ["), "{}", s);
+    assert!(!s.contains("_component_comp, null, ["), "{}", s);
+    // plain elements still get eager children
+    let s = base_compile("<div><span>{{a}}</span></div>");
+    assert!(s.contains(r#"_createElementBlock("div", null, ["#), "{}", s);
+    assert!(!s.contains("_withCtx"), "{}", s);
+}
+
+#[test]
+fn test_dynamic_key_normalize_props() {
+    use compiler::codegen::ScriptMode;
+    let s = base_compile(r#"<div :[key]="val"/>"#);
+    assert!(
+        s.contains(r#"_createElementBlock("div", _normalizeProps({"#),
+        "{}",
+        s
+    );
+    assert!(s.contains("[(key) || '']: val,"), "{}", s);
+    // unknown keys need full diff without dynamicProps
+    assert!(s.contains("}), null, 16 /*FULL_PROPS*/)"), "{}", s);
+    assert!(s.contains("normalizeProps: _normalizeProps"), "{}", s);
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(r#"<div :[key]="val" id="a"/>"#, option);
+    assert!(s.contains("[(_ctx.key) || '']: _ctx.val,"), "{}", s);
+    assert!(s.contains(r#"id: "a","#), "{}", s);
+    assert!(s.contains("16 /*FULL_PROPS*/"), "{}", s);
+}
+
+#[test]
+fn test_component_boolean_prop() {
+    let s = base_compile("<comp disabled/>");
+    assert!(s.contains("_createBlock(_component_comp, {"), "{}", s);
+    assert!(s.contains(r#"disabled: "","#), "{}", s);
+    // static prop needs no patch
+    assert!(!s.contains("/*"), "{}", s);
+    let s = base_compile("<comp disabled foo-bar/>");
+    assert!(s.contains(r#""foo-bar": "","#), "{}", s);
+}
+
+#[test]
+fn test_dynamic_children_comments() {
+    let source = r#"<div><p/><p v-if="a"/><span :id="b"/></div>"#;
+    let option = |is_dev| CompileOption {
+        is_dev,
+        dynamic_children_comments: true,
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option(true));
+    assert!(s.contains("null, /* dynamic children: 1, 2 */ ["), "{}", s);
+    let s = compile_with_option(source, option(false));
+    assert!(!s.contains("dynamic children"), "{}", s);
+    let s = compile_with_option(source, Default::default());
+    assert!(!s.contains("dynamic children"), "{}", s);
+}
+
+#[test]
+fn test_object_spread_value() {
+    use compiler::codegen::ScriptMode;
+    let source = r#"<div :obj="{ ...base, a: 1 }"/>"#;
+    let s = base_compile(source);
+    assert!(s.contains("obj: { ...base, a: 1 },"), "{}", s);
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    // only the spread identifier is prefixed, the literal key is kept
+    assert!(s.contains("obj: { ..._ctx.base, a: 1 },"), "{}", s);
+    assert!(!s.contains("_ctx.a"), "{}", s);
+}
+
+#[test]
+fn test_v_if_comment_only_branch() {
+    let s = base_compile(r#"<!-- x --><template v-if="c"><!-- y --></template>"#);
+    assert!(s.contains(r#"_createCommentVNode(" x ")"#), "{}", s);
+    // the branch is a fragment holding the comment
+    let branch = s.find("? (_openBlock(), _createElementBlock(_Fragment, {");
+    let inner = s.find(r#"_createCommentVNode(" y ")"#);
+    let fallback = s.find(r#": _createCommentVNode("v-if", true)"#);
+    assert!(branch.expect(&s) < inner.expect(&s), "{}", s);
+    assert!(inner.unwrap() < fallback.expect(&s), "{}", s);
+    assert!(s.contains("(c)"), "{}", s);
+}
+
+#[test]
+fn test_inline_handler_event_param() {
+    use compiler::codegen::ScriptMode;
+    let source = r#"<button @click="handle($event)"/>"#;
+    let s = base_compile(source);
+    assert!(s.contains("onClick: $event => (handle($event))"), "{}", s);
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(source, option);
+    // $event is the arrow param, never a ctx property
+    assert!(s.contains("$event => (_ctx.handle($event))"), "{}", s);
+    assert!(!s.contains("_ctx.$event"), "{}", s);
+}
+
+#[test]
+fn test_dynamic_event_name() {
+    let s = base_compile(r#"<div @[ev]="fn"/>"#);
+    assert!(s.contains("[_toHandlerKey(ev)]: fn,"), "{}", s);
+    assert!(s.contains("_normalizeProps({"), "{}", s);
+    // event name is unknown at compile time
+    assert!(s.contains("}), null, 16 /*FULL_PROPS*/)"), "{}", s);
+    assert!(s.contains("toHandlerKey: _toHandlerKey"), "{}", s);
+}
+
+#[test]
+fn test_member_chain_prefix() {
+    use compiler::codegen::ScriptMode;
+    let prefixed = |src| {
+        let option = CompileOption {
+            mode: ScriptMode::Function {
+                prefix_identifier: true,
+                runtime_global_name: "Vue".into(),
+            },
+            ..Default::default()
+        };
+        compile_with_option(src, option)
+    };
+    let s = prefixed("{{ user.profile.name }}");
+    assert!(
+        s.contains("_toDisplayString(_ctx.user.profile.name)"),
+        "{}",
+        s
+    );
+    assert!(
+        !s.contains("_ctx.profile") && !s.contains("_ctx.name"),
+        "{}",
+        s
+    );
+    // string literal member is kept, identifier in brackets is prefixed
+    let s = prefixed("{{ user['name'] }}");
+    assert!(s.contains("_toDisplayString(_ctx.user['name'])"), "{}", s);
+    let s = prefixed("{{ user[key].name }}");
+    assert!(
+        s.contains("_toDisplayString(_ctx.user[_ctx.key].name)"),
+        "{}",
+        s
+    );
+}
+
+#[test]
+fn test_props_keep_template_order() {
+    let s = base_compile(
+        r#"<div id="x" :title="t" class="a" @click="f" :class="b" style="color: red"/>"#,
+    );
+    let keys = ["id: ", "title: ", "class: ", "onClick: ", "style: "];
+    let pos: Vec<_> = keys.iter().map(|k| s.find(k).expect(&s)).collect();
+    assert!(pos.windows(2).all(|w| w[0] < w[1]), "{}", s);
+    // later :class is merged into the first class key
+    assert_eq!(s.matches("class: ").count(), 1, "{}", s);
+    // v-bind object splits props into ordered mergeProps arguments
+    let s = base_compile(r#"<div id="x" v-bind="obj" title="t"/>"#);
+    let keys = ["id: ", "}, obj, {", "title: "];
+    let pos: Vec<_> = keys.iter().map(|k| s.find(k).expect(&s)).collect();
+    assert!(pos.windows(2).all(|w| w[0] < w[1]), "{}", s);
+}
+
+#[test]
+fn test_preserve_prop_order() {
+    let src = r#"<div class="a" id="x" :class="b"/>"#;
+    // default merges later :class into the first class key
+    let s = base_compile(src);
+    assert!(!s.contains("_mergeProps("), "{}", s);
+    assert_eq!(s.matches("class: ").count(), 1, "{}", s);
+    let class = s.find(r#"class: _normalizeClass(["a", b])"#).expect(&s);
+    assert!(class < s.find("id: ").expect(&s), "{}", s);
+    // preserved order keeps :class after id in another mergeProps argument
+    let option = CompileOption {
+        preserve_prop_order: true,
+        ..Default::default()
+    };
+    let s = compile_with_option(src, option);
+    assert!(s.contains("_mergeProps({"), "{}", s);
+    let keys = [r#"class: "a""#, "id: ", "}, {", "class: b"];
+    let pos: Vec<_> = keys.iter().map(|k| s.find(k).expect(&s)).collect();
+    assert!(pos.windows(2).all(|w| w[0] < w[1]), "{}", s);
+    // static style is still parsed to object for mergeProps
+    let s = compile_with_option(
+        r#"<div style="color: red" id="x" :style="s"/>"#,
+        CompileOption {
+            preserve_prop_order: true,
+            ..Default::default()
+        },
+    );
+    assert!(s.contains("color: \"red\""), "{}", s);
+    let keys = ["color: ", "id: ", "}, {", "style: s"];
+    let pos: Vec<_> = keys.iter().map(|k| s.find(k).expect(&s)).collect();
+    assert!(pos.windows(2).all(|w| w[0] < w[1]), "{}", s);
+}
+
+#[test]
+fn test_guard_reactive_v_bind_spread() {
+    let s = base_compile(r#"<div v-bind="obj"/>"#);
+    assert!(
+        s.contains("_normalizeProps(_guardReactiveProps(obj))"),
+        "{}",
+        s
+    );
+    // reserved props do not consume the spread
+    let s = base_compile(r#"<div v-bind="obj" key="k"/>"#);
+    assert!(
+        s.contains("_mergeProps(_guardReactiveProps(obj), {"),
+        "{}",
+        s
+    );
+    // merging with normal props copies the object, no guard needed
+    let s = base_compile(r#"<div v-bind="obj" id="a"/>"#);
+    assert!(s.contains("_mergeProps(obj, {"), "{}", s);
+    assert!(!s.contains("_guardReactiveProps"), "{}", s);
+}
+
+#[test]
+fn test_v_for_normalize_class() {
+    use compiler::codegen::ScriptMode;
+    let s = base_compile(r#"<p v-for="item in list" :class="item.cls"/>"#);
+    let render_list = s.find("_renderList(list, (item) => ").expect(&s);
+    let class = s.find("class: _normalizeClass(item.cls)").expect(&s);
+    assert!(render_list < class, "{}", s);
+    // loop invariant class is normalized once before render returns
+    let s = base_compile(r#"<p v-for="item in list" :class="cls"/>"#);
+    let class = s.find("const _class = _normalizeClass(cls)\n").expect(&s);
+    let ret = s.find("return (_openBlock(").expect(&s);
+    assert!(class < ret, "{}", s);
+    assert!(s.contains("_renderList(list, (item) => "), "{}", s);
+    assert!(s.contains("class: _class"), "{}", s);
+    assert_eq!(s.matches("_normalizeClass(").count(), 1, "{}", s);
+    let s =
+        base_compile(r#"<div><p v-for="a in b" :class="c"/><p v-for="a in b" :class="d"/></div>"#);
+    assert!(s.contains("const _class = _normalizeClass(c)"), "{}", s);
+    assert!(s.contains("const _class1 = _normalizeClass(d)"), "{}", s);
+    assert!(s.contains("class: _class1"), "{}", s);
+    // v-for in v-if is not always rendered
+    let s = base_compile(r#"<p v-if="ok" v-for="item in list" :class="cls"/>"#);
+    assert!(!s.contains("const _class"), "{}", s);
+    assert!(s.contains("class: _normalizeClass(cls)"), "{}", s);
+    // index alias or function call is normalized per item
+    let s = base_compile(r#"<p v-for="(item, i) in list" :class="{ a: i }"/>"#);
+    assert!(s.contains("class: _normalizeClass({ a: i })"), "{}", s);
+    let s = base_compile(r#"<p v-for="item in list" :class="getCls()"/>"#);
+    assert!(s.contains("class: _normalizeClass(getCls())"), "{}", s);
+    // destructured alias
+    let option = || CompileOption {
+        mode: ScriptMode::Module {
+            runtime_module_name: "vue".into(),
+        },
+        ..Default::default()
+    };
+    let s = compile_with_option(r#"<p v-for="{ cls } in list" :class="cls"/>"#, option());
+    assert!(s.contains("class: _normalizeClass(cls)"), "{}", s);
+    let s = compile_with_option(r#"<p v-for="item in list" :class="cls"/>"#, option());
+    assert!(
+        s.contains("const _class = _normalizeClass(_ctx.cls)"),
+        "{}",
+        s
+    );
+}
+
+#[test]
+fn test_optional_chaining_prefixed() {
+    use compiler::codegen::ScriptMode;
+    let option = CompileOption {
+        mode: ScriptMode::Function {
+            prefix_identifier: true,
+            runtime_global_name: "Vue".into(),
+        },
+        optional_chaining: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("{{a.b['x.y'].c}} {{d[1.5]}}", option);
+    assert!(s.contains("_toDisplayString(_ctx.a?.b['x.y']?.c)"), "{}", s);
+    assert!(s.contains("_toDisplayString(_ctx.d[1.5])"), "{}", s);
+}
+
+#[test]
+fn test_elide_unused_params_ctx() {
+    use compiler::codegen::ScriptMode;
+    let option = || CompileOption {
+        mode: ScriptMode::Module {
+            runtime_module_name: "vue".into(),
+        },
+        elide_unused_params: true,
+        ..Default::default()
+    };
+    let s = compile_with_option("<p>{{a}}</p>", option());
+    assert!(s.contains("function render(_ctx) {"), "{}", s);
+    // identifiers merely containing _ctx are not params
+    let s = compile_with_option("<p>{{ Math.max(1, 2) }} {{ '_ctx' }}</p>", option());
+    assert!(s.contains("function render() {"), "{}", s);
+}
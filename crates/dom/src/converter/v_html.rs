@@ -19,7 +19,7 @@ pub fn convert_v_html<'a>(
         let error = CompilationError::extended(DomError::VHtmlWithChildren)
             .with_location(dir.location.clone());
         eh.on_error(error);
-        // TODO remove element children
+        // children are dropped in codegen since innerHTML wins
     }
     let val = dir.expression.take().unwrap().content;
     let props = vec![(Js::str_lit("innerHTML"), Js::simple(val))];
@@ -10,7 +10,7 @@ use compiler::transformer::{
     optimize_text::TextOptimizer,
     pass::{Scope, SharedInfoPasses},
     process_expression::ExpressionProcessor,
-    normalize_props::NormalizeProp,
+    normalize_props::{HoistForClass, NormalizeProp},
     hoist_static::HoistStatic,
 };
 use compiler::converter::BaseConvertInfo;
@@ -25,7 +25,7 @@ pub fn get_dom_pass<'a>(
     let prefix_identifier = opt.transforming().prefix_identifier;
     let shared = chain![
         SlotFlagMarker,
-        HoistStatic::new(opt.cache_handlers),
+        HoistStatic::new(opt.cache_handlers, opt.hoist_static),
         ExpressionProcessor {
             prefix_identifier,
             sfc_info,
@@ -38,6 +38,7 @@ pub fn get_dom_pass<'a>(
         TextOptimizer,
         EntityCollector::default(),
         NormalizeProp,
+        HoistForClass::default(),
         SharedInfoPasses {
             passes: shared,
             shared_info: Scope::default(),
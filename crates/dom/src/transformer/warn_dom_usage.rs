@@ -87,6 +87,7 @@ fn ir_multilicity(ir: &BaseIR) -> Multiplicity {
             }
         }
         IRNode::RenderSlotCall(..) => One, // be lenient
+        IRNode::Hoisted(..) => One,
         IRNode::VNodeCall(vn) => {
             if let Js::Symbol(RuntimeHelper::FRAGMENT) = vn.tag {
                 has_multiple_children(&vn.children)
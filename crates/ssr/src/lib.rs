@@ -1 +1,501 @@
+pub mod ssr_helper {
+    use compiler::flags::RuntimeHelper as RH;
+    pub const SSR_INTERPOLATE: RH = RH(RH::INTERNAL_MAX);
+    pub const SSR_RENDER_VNODE: RH = RH(RH::INTERNAL_MAX + 1);
+    pub const SSR_RENDER_COMPONENT: RH = RH(RH::INTERNAL_MAX + 2);
+    pub const SSR_RENDER_SLOT: RH = RH(RH::INTERNAL_MAX + 3);
+    pub const SSR_RENDER_SLOT_INNER: RH = RH(RH::INTERNAL_MAX + 4);
+    pub const SSR_RENDER_CLASS: RH = RH(RH::INTERNAL_MAX + 5);
+    pub const SSR_RENDER_STYLE: RH = RH(RH::INTERNAL_MAX + 6);
+    pub const SSR_RENDER_ATTRS: RH = RH(RH::INTERNAL_MAX + 7);
+    pub const SSR_RENDER_ATTR: RH = RH(RH::INTERNAL_MAX + 8);
+    pub const SSR_RENDER_DYNAMIC_ATTR: RH = RH(RH::INTERNAL_MAX + 9);
+    pub const SSR_RENDER_LIST: RH = RH(RH::INTERNAL_MAX + 10);
+    pub const SSR_INCLUDE_BOOLEAN_ATTR: RH = RH(RH::INTERNAL_MAX + 11);
+    pub const SSR_LOOSE_EQUAL: RH = RH(RH::INTERNAL_MAX + 12);
+    pub const SSR_LOOSE_CONTAIN: RH = RH(RH::INTERNAL_MAX + 13);
+    pub const SSR_RENDER_DYNAMIC_MODEL: RH = RH(RH::INTERNAL_MAX + 14);
+    pub const SSR_GET_DYNAMIC_MODEL_PROPS: RH = RH(RH::INTERNAL_MAX + 15);
+    pub const SSR_RENDER_TELEPORT: RH = RH(RH::INTERNAL_MAX + 16);
+    pub const SSR_RENDER_SUSPENSE: RH = RH(RH::INTERNAL_MAX + 17);
+    pub const SSR_GET_DIRECTIVE_PROPS: RH = RH(RH::INTERNAL_MAX + 18);
 
+    pub const SSR_HELPER_MAP: &[&str] = &[
+        "ssrInterpolate",
+        "ssrRenderVNode",
+        "ssrRenderComponent",
+        "ssrRenderSlot",
+        "ssrRenderSlotInner",
+        "ssrRenderClass",
+        "ssrRenderStyle",
+        "ssrRenderAttrs",
+        "ssrRenderAttr",
+        "ssrRenderDynamicAttr",
+        "ssrRenderList",
+        "ssrIncludeBooleanAttr",
+        "ssrLooseEqual",
+        "ssrLooseContain",
+        "ssrRenderDynamicModel",
+        "ssrGetDynamicModelProps",
+        "ssrRenderTeleport",
+        "ssrRenderSuspense",
+        "ssrGetDirectiveProps",
+    ];
+
+    /// ssr helpers are imported from server renderer, not the runtime module.
+    pub const SSR_RUNTIME_MODULE: &str = "vue/server-renderer";
+
+    pub fn ssr_helper_source(rh: RH) -> Option<&'static str> {
+        (rh.0 >= RH::INTERNAL_MAX).then(|| SSR_RUNTIME_MODULE)
+    }
+}
+
+/// SSR pushes a text node with interpolations as one template literal
+/// e.g. `a {{x}} b` becomes `a ${_ssrInterpolate(x)} b`
+pub mod ssr_text {
+    use compiler::flags::RuntimeHelper as RH;
+    use compiler::ir::JsExpr as Js;
+    use std::fmt::{self, Write};
+
+    pub fn gen_template_literal<W: Write>(w: &mut W, texts: &[Js]) -> fmt::Result {
+        w.write_char('`')?;
+        gen_text(w, texts)?;
+        w.write_char('`')
+    }
+
+    /// text inside a template literal, without the enclosing backticks
+    pub fn gen_text<W: Write>(w: &mut W, texts: &[Js]) -> fmt::Result {
+        for text in texts {
+            match text {
+                Js::StrLit(s) => escape_template(w, &s.into_string(), true)?,
+                Js::Call(RH::TO_DISPLAY_STRING, args) => {
+                    w.write_str("${_ssrInterpolate(")?;
+                    for arg in args {
+                        gen_expr(w, arg)?;
+                    }
+                    w.write_str(")}")?;
+                }
+                e => {
+                    w.write_str("${")?;
+                    gen_expr(w, e)?;
+                    w.write_char('}')?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn gen_expr<W: Write>(w: &mut W, e: &Js) -> fmt::Result {
+        match e {
+            Js::Src(s) | Js::Param(s) => w.write_str(s),
+            Js::Num(n) => write!(w, "{}", n),
+            Js::Simple(v, _) => v.write_to(w),
+            Js::StrLit(v) => {
+                let mut v = *v;
+                v.be_js_str().write_to(w)
+            }
+            Js::Compound(es) => es.iter().try_for_each(|e| gen_expr(w, e)),
+            // text nodes only contain literals and interpolated expressions
+            _ => Err(fmt::Error),
+        }
+    }
+
+    /// backslash, backtick and `${` are special in template literals.
+    /// Unlike client textContent, pushed SSR text is parsed as HTML so
+    /// decoded `<`, `>`, `&` and `"` must be escaped again, but not in comments.
+    pub fn escape_template<W: Write>(w: &mut W, s: &str, escape_html: bool) -> fmt::Result {
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '<' if escape_html => w.write_str("&lt;")?,
+                '>' if escape_html => w.write_str("&gt;")?,
+                '&' if escape_html => w.write_str("&amp;")?,
+                '"' if escape_html => w.write_str("&quot;")?,
+                '\\' | '`' => {
+                    w.write_char('\\')?;
+                    w.write_char(c)?;
+                }
+                '$' if chars.peek() == Some(&'{') => w.write_str("\\$")?,
+                _ => w.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generates `ssrRender` pushing the template as one HTML template literal,
+/// e.g. `_push(`<p>${_ssrInterpolate(_ctx.msg)}</p>`)`.
+/// Only text, comments and plain elements with static attributes are
+/// supported now, other nodes fail with `io::ErrorKind::InvalidData`.
+pub mod ssr_codegen {
+    use super::ssr_helper::SSR_RUNTIME_MODULE;
+    use super::ssr_text::{escape_template, gen_text};
+    use compiler::codegen::{CodegenResult, ScriptMode};
+    use compiler::compiler::CompileOption;
+    use compiler::converter::{BaseIR, BaseRoot};
+    use compiler::flags::RuntimeHelper as RH;
+    use compiler::ir::{IRNode, JsExpr as Js};
+    use compiler::transformer::BaseVNode;
+    use std::fmt::{self, Write};
+    use std::io;
+
+    const UNSUPPORTED: &str =
+        "SSR codegen only supports text, comments and plain elements with static attributes.";
+
+    pub fn generate_ssr<W: io::Write>(
+        root: &BaseRoot,
+        option: &CompileOption,
+        mut writer: W,
+    ) -> io::Result<CodegenResult> {
+        let mut html = HtmlWriter {
+            html: String::new(),
+            is_void_tag: option.is_void_tag,
+            interpolated: false,
+        };
+        let mut out = String::new();
+        html.gen_root(&root.body)
+            .and_then(|_| gen_render(&mut out, &html, option))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, UNSUPPORTED))?;
+        writer.write_all(out.as_bytes())?;
+        Ok(CodegenResult::default())
+    }
+
+    fn gen_render(w: &mut String, html: &HtmlWriter, option: &CompileOption) -> fmt::Result {
+        let module = SSR_RUNTIME_MODULE;
+        match &option.mode {
+            ScriptMode::Module { .. } => {
+                if html.interpolated {
+                    let rh = "ssrInterpolate";
+                    writeln!(w, "import {{ {0} as _{0} }} from \"{1}\"\n", rh, module)?;
+                }
+                w.write_str("export function ssrRender(_ctx, _push, _parent, _attrs) {")?;
+            }
+            ScriptMode::Function { .. } | ScriptMode::Cjs { .. } => {
+                if html.interpolated {
+                    let rh = "ssrInterpolate";
+                    writeln!(w, "const {{ {0}: _{0} }} = require(\"{1}\")\n", rh, module)?;
+                }
+                if matches!(option.mode, ScriptMode::Function { .. }) {
+                    w.write_str("return ")?;
+                }
+                w.write_str("function ssrRender(_ctx, _push, _parent, _attrs) {")?;
+            }
+        }
+        let with_scope = !option.transforming().prefix_identifier;
+        let indent = if with_scope {
+            w.write_str("\n  with (_ctx) {")?;
+            "\n    "
+        } else {
+            "\n  "
+        };
+        if !html.html.is_empty() {
+            write!(w, "{}_push(`{}`)", indent, html.html)?;
+        }
+        if with_scope {
+            w.write_str("\n  }")?;
+        }
+        w.write_str("\n}")?;
+        if matches!(option.mode, ScriptMode::Cjs { .. }) {
+            w.write_str("\nmodule.exports = { ssrRender }")?;
+        }
+        Ok(())
+    }
+
+    /// collects HTML of the template, interpolations are `${}` in it
+    struct HtmlWriter {
+        html: String,
+        is_void_tag: fn(&str) -> bool,
+        interpolated: bool,
+    }
+
+    impl HtmlWriter {
+        fn gen_root(&mut self, body: &[BaseIR]) -> fmt::Result {
+            // multiple roots are rendered as a fragment
+            if body.len() > 1 {
+                self.html.push_str("<!--[-->");
+            }
+            for ir in body {
+                self.gen_ir(ir)?;
+            }
+            if body.len() > 1 {
+                self.html.push_str("<!--]-->");
+            }
+            Ok(())
+        }
+        fn gen_ir(&mut self, ir: &BaseIR) -> fmt::Result {
+            match ir {
+                IRNode::TextCall(t) => {
+                    let texts = &t.texts;
+                    self.interpolated |= texts
+                        .iter()
+                        .any(|t| matches!(t, Js::Call(RH::TO_DISPLAY_STRING, _)));
+                    gen_text(&mut self.html, texts)
+                }
+                IRNode::VNodeCall(v) => self.gen_element(v),
+                IRNode::CommentCall(c) => {
+                    self.html.push_str("<!--");
+                    escape_template(&mut self.html, c, false)?;
+                    self.html.push_str("-->");
+                    Ok(())
+                }
+                _ => Err(fmt::Error),
+            }
+        }
+        fn gen_element(&mut self, v: &BaseVNode) -> fmt::Result {
+            let tag = match &v.tag {
+                Js::StrLit(tag) if !v.is_component && v.directives.is_empty() => *tag,
+                _ => return Err(fmt::Error),
+            };
+            let tag = tag.into_string();
+            write!(self.html, "<{}", tag)?;
+            match &v.props {
+                None => (),
+                Some(Js::Props(props)) => {
+                    for prop in props {
+                        self.gen_attr(prop)?;
+                    }
+                }
+                Some(_) => return Err(fmt::Error),
+            }
+            self.html.push('>');
+            if (self.is_void_tag)(&tag) {
+                return Ok(());
+            }
+            for child in v.children.iter() {
+                self.gen_ir(child)?;
+            }
+            write!(self.html, "</{}>", tag)
+        }
+        fn gen_attr(&mut self, prop: &(Js, Js)) -> fmt::Result {
+            let (key, val) = match prop {
+                (Js::StrLit(k), Js::StrLit(v)) => (k, v),
+                _ => return Err(fmt::Error),
+            };
+            write!(self.html, " {}=\"", key.into_string())?;
+            escape_template(&mut self.html, &val.into_string(), true)?;
+            self.html.push('"');
+            Ok(())
+        }
+    }
+}
+
+use compiler::codegen::{CodeGen, CodeGenInfo, CodeGenerator, CodegenResult};
+use compiler::compiler::{get_base_passes, CompileOption};
+use compiler::converter::{BaseConverter, Converter};
+use compiler::parser::Parser;
+use compiler::scanner::Scanner;
+use compiler::transformer::{BaseTransformer, Transformer};
+use compiler::SFCInfo;
+use std::io;
+
+/// Compiles client render and `ssrRender` of an isomorphic template together.
+/// Scan, parse, convert and transform run once and both outputs are generated
+/// from the same IR, client render to `client` and `ssrRender` to `ssr`.
+pub fn compile_both<'a, W: io::Write>(
+    source: &'a str,
+    option: &CompileOption,
+    sfc_info: &'a SFCInfo<'a>,
+    client: W,
+    ssr: W,
+) -> io::Result<(CodegenResult, CodegenResult)> {
+    let eh = option.error_handler.clone();
+    let tokens = Scanner::new(option.scanning()).scan(source, eh.clone());
+    let ast = Parser::new(option.parsing()).parse(tokens, eh.clone());
+    let mut ir = BaseConverter::new(eh, option.converting()).convert_ir(ast, sfc_info);
+    BaseTransformer::transform(&mut ir, get_base_passes(sfc_info, option));
+    // ssr only reads the IR, client codegen consumes it afterwards
+    let ssr_result = ssr_codegen::generate_ssr(&ir, option, ssr)?;
+    let generator = CodeGen::new(option.codegen());
+    let info = CodeGenInfo {
+        writer: client,
+        sfc_info,
+    };
+    let client_result = generator.generate(ir, info)?;
+    Ok((client_result, ssr_result))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ssr_helper::*;
+    use super::ssr_codegen::generate_ssr;
+    use super::compile_both;
+    use compiler::codegen::ScriptMode;
+    use compiler::compiler::{get_base_passes, BaseCompiler, CompileOption, TemplateCompiler};
+    use compiler::converter::{BaseConvertInfo, BaseRoot};
+    use compiler::transformer::CorePass;
+    use compiler::{Chain, SFCInfo};
+
+    struct CollectSsrHelper;
+    impl<'a> CorePass<BaseConvertInfo<'a>> for CollectSsrHelper {
+        fn exit_root(&mut self, r: &mut BaseRoot<'a>) {
+            r.top_scope.helpers.collect(SSR_INTERPOLATE);
+        }
+    }
+
+    fn get_passes<'a>(
+        sfc_info: &'a SFCInfo<'a>,
+        opt: &CompileOption,
+    ) -> impl CorePass<BaseConvertInfo<'a>> {
+        // base passes exit root first so collected helpers are already in scope
+        Chain {
+            first: CollectSsrHelper,
+            second: get_base_passes(sfc_info, opt),
+        }
+    }
+
+    #[test]
+    fn test_ssr_helper_import() {
+        let option = CompileOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            helper_strs: SSR_HELPER_MAP,
+            helper_source: ssr_helper_source,
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_passes, option);
+        let ret = compiler.compile("<p>{{a}}</p>", &sfc_info).unwrap();
+        let s = String::from_utf8(ret).unwrap();
+        assert!(s.contains("toDisplayString as _toDisplayString"), "{}", s);
+        assert!(s.contains("} from \"vue\""), "{}", s);
+        assert!(s.contains("ssrInterpolate as _ssrInterpolate"), "{}", s);
+        assert!(s.contains("} from \"vue/server-renderer\""), "{}", s);
+        assert_eq!(s.matches("import {").count(), 2, "{}", s);
+    }
+
+    fn ssr_compile(src: &str) -> String {
+        let option = || CompileOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            is_void_tag: |tag| tag == "br",
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option());
+        let tokens = compiler.scan(src);
+        let ast = compiler.parse(tokens);
+        let mut ir = compiler.convert(ast, &sfc_info);
+        compiler.transform(&mut ir, &sfc_info);
+        let mut out = vec![];
+        generate_ssr(&ir, &option(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_ssr_render() {
+        let s = ssr_compile("<p class=\"a\">{{x}}</p><br><!--c-->");
+        assert!(
+            s.starts_with(
+                "import { ssrInterpolate as _ssrInterpolate } from \"vue/server-renderer\"\n\n"
+            ),
+            "{}",
+            s
+        );
+        assert!(
+            s.contains("export function ssrRender(_ctx, _push, _parent, _attrs) {\n  _push(`"),
+            "{}",
+            s
+        );
+        assert!(
+            s.contains("_push(`<!--[--><p class=\"a\">${_ssrInterpolate(_ctx.x)}</p><br><!--c--><!--]-->`)"),
+            "{}",
+            s
+        );
+        let s = ssr_compile("<p>static</p>");
+        assert!(!s.contains("import"), "{}", s);
+        assert!(s.contains("_push(`<p>static</p>`)"), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_unsupported() {
+        let option = CompileOption::default();
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, Default::default());
+        let tokens = compiler.scan("<p v-if=\"a\"/>");
+        let ir = compiler.convert(compiler.parse(tokens), &sfc_info);
+        let err = generate_ssr(&ir, &option, vec![]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_ssr_text_template_literal() {
+        let s = ssr_compile("a {{x}} b {{y}}");
+        assert!(
+            s.contains("_push(`a ${_ssrInterpolate(_ctx.x)} b ${_ssrInterpolate(_ctx.y)}`)"),
+            "{}",
+            s
+        );
+        let s = ssr_compile("`${a}` \\ {{x}}");
+        assert!(
+            s.contains(r"_push(`\`\${a}\` \\ ${_ssrInterpolate(_ctx.x)}`)"),
+            "{}",
+            s
+        );
+    }
+
+    #[test]
+    fn test_ssr_text_html_escape() {
+        let s = ssr_compile("a < b & c");
+        assert!(s.contains("_push(`a &lt; b &amp; c`)"), "{}", s);
+        let s = ssr_compile("&lt;{{x}}&gt;");
+        assert!(
+            s.contains("_push(`&lt;${_ssrInterpolate(_ctx.x)}&gt;`)"),
+            "{}",
+            s
+        );
+        let s = ssr_compile("<p title='&quot;a&quot; &amp; b'>a < b & c</p>");
+        assert!(
+            s.contains(r#"_push(`<p title="&quot;a&quot; &amp; b">a &lt; b &amp; c</p>`)"#),
+            "{}",
+            s
+        );
+        // client sets textContent, the literal is only JS escaped
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, Default::default());
+        let ret = compiler.compile("<p>a < b & c</p>", &sfc_info).unwrap();
+        let s = String::from_utf8(ret).unwrap();
+        assert!(s.contains(r#""a < b & c""#), "{}", s);
+        assert!(!s.contains("&lt;"), "{}", s);
+    }
+
+    #[test]
+    fn test_compile_both() {
+        let option = CompileOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let (mut client, mut ssr) = (vec![], vec![]);
+        let src = "<p class=\"a\">{{ msg }}</p>";
+        compile_both(src, &option, &sfc_info, &mut client, &mut ssr).unwrap();
+        let client = String::from_utf8(client).unwrap();
+        let ssr = String::from_utf8(ssr).unwrap();
+        // both render the same element with the same prefixed expression
+        assert!(
+            client.contains("function render(_ctx, _cache) {"),
+            "{}",
+            client
+        );
+        assert!(client.contains("_createElementBlock(\"p\""), "{}", client);
+        assert!(client.contains("class: \"a\""), "{}", client);
+        assert!(client.contains("_toDisplayString(_ctx.msg)"), "{}", client);
+        assert!(
+            ssr.contains("export function ssrRender(_ctx, _push, _parent, _attrs) {"),
+            "{}",
+            ssr
+        );
+        assert!(
+            ssr.contains("_push(`<p class=\"a\">${_ssrInterpolate(_ctx.msg)}</p>`)"),
+            "{}",
+            ssr
+        );
+        let (mut client, mut ssr) = (vec![], vec![]);
+        let src = "<p v-if=\"ok\"/>";
+        let err = compile_both(src, &option, &sfc_info, &mut client, &mut ssr).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}